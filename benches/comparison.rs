@@ -26,7 +26,8 @@ fn benchmark_bitvector_simd3(c: &mut Criterion) {
     c.bench_function("bitvec_simd(this crate) resize false", |b| {
         b.iter(|| {
             let mut b1 = bitvec_simd::BitVec::ones(100_000);
-            black_box(b1.resize(200_000, false));
+            b1.resize(200_000, false);
+            black_box(b1);
         })
     });
 }
@@ -35,7 +36,8 @@ fn benchmark_bitvector_simd4(c: &mut Criterion) {
     c.bench_function("bitvec_simd(this crate) resize true", |b| {
         b.iter(|| {
             let mut b1 = bitvec_simd::BitVec::ones(100_000);
-            black_box(b1.resize(200_000, true));
+            b1.resize(200_000, true);
+            black_box(b1);
         })
     });
 }
@@ -90,6 +92,99 @@ fn benchmark_bitvector_simd_u64x4(c: &mut Criterion) {
     });
 }
 
+fn benchmark_bitvector_simd_u8x32(c: &mut Criterion) {
+    let b1 = bitvec_simd::BitVecSimd::<wide::u8x32, 32>::ones(100_000);
+    let b2 = bitvec_simd::BitVecSimd::<wide::u8x32, 32>::zeros(100_000);
+    c.bench_function("bitvec_simd_u8x32(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and_cloned(&b2));
+        })
+    });
+}
+
+fn benchmark_bitvector_simd_u16x16(c: &mut Criterion) {
+    let b1 = bitvec_simd::BitVecSimd::<wide::u16x16, 16>::ones(100_000);
+    let b2 = bitvec_simd::BitVecSimd::<wide::u16x16, 16>::zeros(100_000);
+    c.bench_function("bitvec_simd_u16x16(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and_cloned(&b2));
+        })
+    });
+}
+
+fn benchmark_bitvector_simd_u64x4x2(c: &mut Criterion) {
+    let b1 = bitvec_simd::BitVecSimd::<bitvec_simd::u64x4x2, 8>::ones(100_000);
+    let b2 = bitvec_simd::BitVecSimd::<bitvec_simd::u64x4x2, 8>::zeros(100_000);
+    c.bench_function("bitvec_simd_u64x4x2(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and_cloned(&b2));
+        })
+    });
+}
+
+fn benchmark_bitvector_simd_u64(c: &mut Criterion) {
+    let b1 = bitvec_simd::BitVecSimd::<u64, 1>::ones(100_000);
+    let b2 = bitvec_simd::BitVecSimd::<u64, 1>::zeros(100_000);
+    c.bench_function("bitvec_simd_u64(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and_cloned(&b2));
+        })
+    });
+}
+
+fn benchmark_bitvector_simd_u128(c: &mut Criterion) {
+    let b1 = bitvec_simd::BitVecSimd::<u128, 1>::ones(100_000);
+    let b2 = bitvec_simd::BitVecSimd::<u128, 1>::zeros(100_000);
+    c.bench_function("bitvec_simd_u128(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and_cloned(&b2));
+        })
+    });
+}
+
+#[cfg(feature = "std")]
+fn benchmark_bitvector_simd_dyn(c: &mut Criterion) {
+    let b1 = bitvec_simd::DynBitVec::new(bitvec_simd::BitVec::ones(100_000));
+    let b2 = bitvec_simd::DynBitVec::new(bitvec_simd::BitVec::zeros(100_000));
+    c.bench_function("bitvec_simd_dyn(this crate)", |b| {
+        b.iter(|| {
+            black_box(b1.and(&b2));
+        })
+    });
+}
+
+// `DynBitVec` needs std for CPU feature detection
+#[cfg(not(feature = "std"))]
+fn benchmark_bitvector_simd_dyn(_: &mut Criterion) {}
+
+fn benchmark_count_ones(c: &mut Criterion) {
+    let b1: bitvec_simd::BitVec = (0..100_000).map(|x| x % 3 == 0).collect();
+    c.bench_function("bitvec_simd(this crate) count_ones", |b| {
+        b.iter(|| {
+            black_box(b1.count_ones());
+        })
+    });
+}
+
+fn benchmark_count_ones_u8x32(c: &mut Criterion) {
+    let b1: bitvec_simd::BitVecSimd<wide::u8x32, 32> = (0..100_000).map(|x| x % 3 == 0).collect();
+    c.bench_function("bitvec_simd_u8x32(this crate) count_ones", |b| {
+        b.iter(|| {
+            black_box(b1.count_ones());
+        })
+    });
+}
+
+// the per-element loop `count_ones` used before blocks had their own popcount
+fn benchmark_count_ones_scalar(c: &mut Criterion) {
+    let b1: bitvec_simd::BitVec = (0..100_000).map(|x| x % 3 == 0).collect();
+    c.bench_function("bitvec_simd(this crate) count_ones scalar loop", |b| {
+        b.iter(|| {
+            black_box(b1.as_elements().iter().map(|x| x.count_ones()).sum::<u32>());
+        })
+    });
+}
+
 fn benchmark_bitvector_simd2_u16x8(c: &mut Criterion) {
     c.bench_function("bitvec_simd_u16x8(this crate) with creation", |b| {
         b.iter(|| {
@@ -104,7 +199,8 @@ fn benchmark_bitvector_simd3_u16x8(c: &mut Criterion) {
     c.bench_function("bitvec_simd_u16x8(this crate) resize false", |b| {
         b.iter(|| {
             let mut b1 = bitvec_simd::BitVecSimd::<wide::u16x8, 8>::ones(100_000);
-            black_box(b1.resize(200_000, false));
+            b1.resize(200_000, false);
+            black_box(b1);
         })
     });
 }
@@ -113,7 +209,8 @@ fn benchmark_bitvector_simd4_u16x8(c: &mut Criterion) {
     c.bench_function("bitvec_simd_u16x8(this crate) resize true", |b| {
         b.iter(|| {
             let mut b1 = bitvec_simd::BitVecSimd::<wide::u16x8, 8>::ones(100_000);
-            black_box(b1.resize(200_000, true));
+            b1.resize(200_000, true);
+            black_box(b1);
         })
     });
 }
@@ -162,7 +259,8 @@ fn benchmark_bitvector_bitvec_n3(c: &mut Criterion) {
     c.bench_function("bitvec 1.0 resize false", |b| {
         b.iter(|| {
             let mut b1 = bitvec::bitvec![usize, bitvec::order::Msb0; 1; 100_000];
-            black_box(b1.resize(200_000, false));
+            b1.resize(200_000, false);
+            black_box(b1);
         })
     });
 }
@@ -171,7 +269,8 @@ fn benchmark_bitvector_bitvec_n4(c: &mut Criterion) {
     c.bench_function("bitvec 1.0 resize true", |b| {
         b.iter(|| {
             let mut b1 = bitvec::bitvec![usize, bitvec::order::Msb0; 1; 100_000];
-            black_box(b1.resize(200_000, true));
+            b1.resize(200_000, true);
+            black_box(b1);
         })
     });
 }
@@ -234,6 +333,12 @@ criterion_group!(
     benchmark_bitvector_simd_u32x8,
     benchmark_bitvector_simd_u64x2,
     benchmark_bitvector_simd_u64x4,
+    benchmark_bitvector_simd_u8x32,
+    benchmark_bitvector_simd_u16x16,
+    benchmark_bitvector_simd_u64x4x2,
+    benchmark_bitvector_simd_u64,
+    benchmark_bitvector_simd_u128,
+    benchmark_bitvector_simd_dyn,
     benchmark_bitvector_bitvec,
     benchmark_bitvector_bitvec_n,
     benchmark_stdvec,
@@ -260,9 +365,16 @@ criterion_group!(
     benchmark_bitvector_simd4_u16x8,
     benchmark_bitvector_bitvec_n4
 );
+criterion_group!(
+    count_ones_benches,
+    benchmark_count_ones,
+    benchmark_count_ones_u8x32,
+    benchmark_count_ones_scalar
+);
 criterion_main!(
     normal_benches,
     with_creation_benches,
     resize_false_benches,
-    resize_true_benches
+    resize_true_benches,
+    count_ones_benches
 );
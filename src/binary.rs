@@ -0,0 +1,241 @@
+// Compact, stable binary format of a bitvec, see `BitVecSimd::to_bytes`.
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+#[cfg(feature = "std")]
+use std::io::{self, Read, Write};
+
+use core::{cmp::Ordering, fmt};
+
+use crate::{BitBlock, BitBlockElement, BitVecSimd};
+
+const MAGIC: [u8; 4] = *b"BVSD";
+const VERSION: u8 = 1;
+const HEADER_LEN: usize = 16;
+
+/// Error returned by [`BitVecSimd::from_bytes`], `BitVecSimd::read_from` (with the `std`
+/// feature) and [`BitVecSimd::from_roaring`] on malformed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DecodeError {
+    /// The input doesn't start with the expected magic bytes or cookie.
+    BadMagic,
+    /// The format version isn't supported by this crate.
+    UnsupportedVersion(u8),
+    /// The element width isn't 1, 2, 4, 8 or 16 bytes.
+    UnsupportedElementWidth(u8),
+    /// The reserved header bytes aren't zero.
+    ReservedBytes,
+    /// `nbits` doesn't fit in a `usize`.
+    TooLong(u64),
+    /// The input ends before the header or the payload does.
+    UnexpectedEnd,
+    /// The input has bytes after the payload.
+    TrailingBytes,
+    /// A bit at or above `nbits` is set.
+    BitsBeyondLen,
+    /// The Roaring container with this key is out of order or inconsistent.
+    InvalidContainer(u16),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DecodeError::BadMagic => write!(f, "bad magic bytes"),
+            DecodeError::UnsupportedVersion(v) => write!(f, "unsupported format version {}", v),
+            DecodeError::UnsupportedElementWidth(w) => {
+                write!(f, "unsupported element width {} bytes", w)
+            }
+            DecodeError::ReservedBytes => write!(f, "non-zero reserved header bytes"),
+            DecodeError::TooLong(nbits) => write!(f, "{} bits don't fit in usize", nbits),
+            DecodeError::UnexpectedEnd => write!(f, "unexpected end of input"),
+            DecodeError::TrailingBytes => write!(f, "trailing bytes after payload"),
+            DecodeError::BitsBeyondLen => write!(f, "set bits beyond nbits"),
+            DecodeError::InvalidContainer(key) => write!(f, "invalid container with key {}", key),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DecodeError {}
+
+#[cfg(feature = "std")]
+impl From<DecodeError> for io::Error {
+    fn from(e: DecodeError) -> Self {
+        match e {
+            DecodeError::UnexpectedEnd => io::Error::new(io::ErrorKind::UnexpectedEof, e),
+            _ => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
+
+// returns (nbits, payload length in bytes)
+fn parse_header(header: &[u8]) -> Result<(usize, usize), DecodeError> {
+    if header.len() < HEADER_LEN {
+        return Err(DecodeError::UnexpectedEnd);
+    }
+    if header[0..4] != MAGIC {
+        return Err(DecodeError::BadMagic);
+    }
+    if header[4] != VERSION {
+        return Err(DecodeError::UnsupportedVersion(header[4]));
+    }
+    let width = header[5];
+    if !matches!(width, 1 | 2 | 4 | 8 | 16) {
+        return Err(DecodeError::UnsupportedElementWidth(width));
+    }
+    // reserved for later versions, which may give them a meaning this version doesn't know
+    if header[6..8] != [0, 0] {
+        return Err(DecodeError::ReservedBytes);
+    }
+    let mut nbits = [0u8; 8];
+    nbits.copy_from_slice(&header[8..16]);
+    let nbits = u64::from_le_bytes(nbits);
+    let nbits_usize = usize::try_from(nbits).map_err(|_| DecodeError::TooLong(nbits))?;
+    let element_bits = width as usize * 8;
+    let payload_len =
+        (nbits_usize / element_bits + (nbits_usize % element_bits != 0) as usize) * width as usize;
+    Ok((nbits_usize, payload_len))
+}
+
+impl<B, const L: usize> BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn header(&self) -> [u8; HEADER_LEN] {
+        let mut header = [0u8; HEADER_LEN];
+        header[0..4].copy_from_slice(&MAGIC);
+        header[4] = VERSION;
+        header[5] = (B::ELEMENT_BIT_WIDTH / 8) as u8;
+        header[8..16].copy_from_slice(&(self.nbits as u64).to_le_bytes());
+        header
+    }
+
+    // number of elements written to the payload
+    fn payload_elements(&self) -> usize {
+        (self.nbits + B::ELEMENT_BIT_WIDTH - 1) / B::ELEMENT_BIT_WIDTH
+    }
+
+    fn decode_payload(payload: &[u8], nbits: usize) -> Result<Self, DecodeError> {
+        // padding after the last bit must be zero
+        let nbytes = (nbits + 7) / 8;
+        if payload[nbytes..].iter().any(|x| *x != 0)
+            || (nbits % 8 != 0 && payload[nbytes - 1] >> (nbits % 8) != 0)
+        {
+            return Err(DecodeError::BitsBeyondLen);
+        }
+        Ok(Self::from_le_bytes(payload, nbits))
+    }
+
+    /// Encode this bitvec in a compact binary format:
+    ///
+    /// | offset | size | content                                                  |
+    /// |--------|------|----------------------------------------------------------|
+    /// | 0      | 4    | magic `b"BVSD"`                                          |
+    /// | 4      | 1    | format version, currently 1                              |
+    /// | 5      | 1    | element width in bytes: 1, 2, 4, 8 or 16                 |
+    /// | 6      | 2    | reserved, must be zero                                   |
+    /// | 8      | 8    | `nbits` as a little-endian `u64`                         |
+    /// | 16     | ...  | `ceil(nbits / element bits)` little-endian elements      |
+    ///
+    /// Bit `i` is bit `i % 8` of payload byte `i / 8` whatever the element width, so the output
+    /// can be decoded with [`BitVecSimd::from_bytes`] by a bitvec of any block type.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::{BitVec, BitVecSimd};
+    /// use wide::u16x8;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 200]);
+    /// let bytes = bitvec.to_bytes();
+    /// assert_eq!(bytes.len(), 16 + 32);
+    /// let bitvec2 = BitVecSimd::<u16x8, 8>::from_bytes(&bytes).unwrap();
+    /// assert_eq!(bitvec2.len(), 201);
+    /// assert_eq!(bitvec2.to_usizes(), vec![1, 3, 200]);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let element_bytes = B::ELEMENT_BIT_WIDTH / 8;
+        let n = self.payload_elements();
+        let mut bytes = Vec::with_capacity(HEADER_LEN + n * element_bytes);
+        bytes.extend_from_slice(&self.header());
+        bytes.resize(HEADER_LEN + n * element_bytes, 0);
+        let elements = self.storage.iter().flat_map(|x| x.to_array()).take(n);
+        for (out, x) in bytes[HEADER_LEN..]
+            .chunks_exact_mut(element_bytes)
+            .zip(elements)
+        {
+            x.write_le_slice(out);
+        }
+        bytes
+    }
+
+    /// Decode a bitvec written by [`BitVecSimd::to_bytes`] or `BitVecSimd::write_to`.
+    ///
+    /// Returns an error if the header is malformed, if `bytes` is shorter or longer than the
+    /// encoded bitvec, or if any bit at or above `nbits` is set.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let (nbits, payload_len) = parse_header(bytes)?;
+        let payload = &bytes[HEADER_LEN..];
+        match payload.len().cmp(&payload_len) {
+            Ordering::Less => Err(DecodeError::UnexpectedEnd),
+            Ordering::Greater => Err(DecodeError::TrailingBytes),
+            Ordering::Equal => Self::decode_payload(payload, nbits),
+        }
+    }
+
+    /// Write this bitvec to `writer` in the same format as [`BitVecSimd::to_bytes`].
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 200]);
+    /// let mut file = Vec::new();
+    /// bitvec.write_to(&mut file).unwrap();
+    /// bitvec.write_to(&mut file).unwrap();
+    /// let mut reader = &file[..];
+    /// assert_eq!(BitVec::read_from(&mut reader).unwrap(), bitvec);
+    /// assert_eq!(BitVec::read_from(&mut reader).unwrap(), bitvec);
+    /// assert!(reader.is_empty());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        writer.write_all(&self.header())?;
+        let element_bytes = B::ELEMENT_BIT_WIDTH / 8;
+        let mut n = self.payload_elements();
+        let mut buf = vec![0u8; B::LANES * element_bytes];
+        for block in self.storage.iter() {
+            let len = n.min(B::LANES);
+            for (out, x) in buf.chunks_exact_mut(element_bytes).zip(block.to_array()) {
+                x.write_le_slice(out);
+            }
+            writer.write_all(&buf[..len * element_bytes])?;
+            n -= len;
+        }
+        Ok(())
+    }
+
+    /// Read one bitvec written by [`BitVecSimd::write_to`] from `reader`.
+    ///
+    /// Exactly the bytes of the encoded bitvec are consumed, so several bitvecs can be read
+    /// from one stream. Malformed input is reported as [`io::ErrorKind::InvalidData`] wrapping a
+    /// [`DecodeError`].
+    #[cfg(feature = "std")]
+    pub fn read_from<R: Read>(mut reader: R) -> io::Result<Self> {
+        let mut header = [0u8; HEADER_LEN];
+        reader.read_exact(&mut header)?;
+        let (nbits, payload_len) = parse_header(&header)?;
+        // don't trust the header with a huge allocation, let the buffer grow with the input
+        let mut payload = Vec::new();
+        reader.take(payload_len as u64).read_to_end(&mut payload)?;
+        if payload.len() < payload_len {
+            return Err(DecodeError::UnexpectedEnd.into());
+        }
+        Ok(Self::decode_payload(&payload, nbits)?)
+    }
+}
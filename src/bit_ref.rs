@@ -0,0 +1,206 @@
+use core::{
+    fmt,
+    iter::FusedIterator,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use crate::{BitBlock, BitBlockElement, BitVecSimd};
+
+/// A mutable reference to one bit of a [`BitVecSimd`].
+///
+/// Created by [`BitVecSimd::get_mut`] and [`BitVecSimd::iter_mut`]. The bit is read when the
+/// reference is created, and written back when it is dropped if it was changed.
+pub struct BitRefMut<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    // references from `iter_mut` may share an element, so it is only accessed through a raw
+    // pointer, which also keeps them on one thread
+    element: *mut B::Element,
+    bit: u32,
+    old: bool,
+    value: bool,
+    _marker: PhantomData<&'a mut BitVecSimd<B, L>>,
+}
+
+impl<'a, B, const L: usize> BitRefMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    // SAFETY: `element` must be valid for reads and writes during `'a`, and no other reference
+    // to bit `bit` of it may exist
+    unsafe fn new(element: *mut B::Element, bit: u32) -> Self {
+        let value = *element & B::ONE_ELEMENT.wrapping_shl(bit) != B::ZERO_ELEMENT;
+        Self {
+            element,
+            bit,
+            old: value,
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'a, B, const L: usize> Deref for BitRefMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Target = bool;
+
+    fn deref(&self) -> &bool {
+        &self.value
+    }
+}
+
+impl<'a, B, const L: usize> DerefMut for BitRefMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn deref_mut(&mut self) -> &mut bool {
+        &mut self.value
+    }
+}
+
+impl<'a, B, const L: usize> Drop for BitRefMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn drop(&mut self) {
+        if self.value != self.old {
+            // SAFETY: guaranteed by the creator, other bits of the element are read again so
+            // writes through other references to the same element aren't lost
+            unsafe { *self.element = *self.element ^ B::ONE_ELEMENT.wrapping_shl(self.bit) };
+        }
+    }
+}
+
+impl<'a, B, const L: usize> fmt::Debug for BitRefMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&self.value, f)
+    }
+}
+
+/// Iterator over mutable references to every bit of a [`BitVecSimd`].
+///
+/// Created by [`BitVecSimd::iter_mut`].
+pub struct IterMut<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    elements: *mut B::Element,
+    start: usize,
+    end: usize,
+    _marker: PhantomData<&'a mut BitVecSimd<B, L>>,
+}
+
+impl<'a, B, const L: usize> IterMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn bit_ref(&self, index: usize) -> BitRefMut<'a, B, L> {
+        // SAFETY: `index` is less than the length of the bitvec borrowed for `'a`, and every
+        // index is yielded once
+        unsafe {
+            BitRefMut::new(
+                self.elements.add(index / B::ELEMENT_BIT_WIDTH),
+                (index % B::ELEMENT_BIT_WIDTH) as u32,
+            )
+        }
+    }
+}
+
+impl<'a, B, const L: usize> Iterator for IterMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Item = BitRefMut<'a, B, L>;
+
+    fn next(&mut self) -> Option<BitRefMut<'a, B, L>> {
+        if self.start == self.end {
+            return None;
+        }
+        self.start += 1;
+        Some(self.bit_ref(self.start - 1))
+    }
+
+    #[inline]
+    fn nth(&mut self, n: usize) -> Option<BitRefMut<'a, B, L>> {
+        self.start = self.end.min(self.start.saturating_add(n));
+        self.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.end - self.start, Some(self.end - self.start))
+    }
+}
+
+impl<'a, B, const L: usize> DoubleEndedIterator for IterMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn next_back(&mut self) -> Option<BitRefMut<'a, B, L>> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(self.bit_ref(self.end))
+    }
+}
+
+impl<'a, B, const L: usize> ExactSizeIterator for IterMut<'a, B, L> where B: BitBlock<L> {}
+
+impl<'a, B, const L: usize> FusedIterator for IterMut<'a, B, L> where B: BitBlock<L> {}
+
+impl<B, const L: usize> BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    /// Return a mutable reference to bit `index`, or `None` if index >= len.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::zeros(10);
+    /// *bitvec.get_mut(3).unwrap() = true;
+    /// if let Some(mut bit) = bitvec.get_mut(4) {
+    ///     *bit = !*bit;
+    /// }
+    /// assert!(bitvec.get_mut(10).is_none());
+    /// assert_eq!(bitvec.to_usizes(), vec![3, 4]);
+    /// ```
+    pub fn get_mut(&mut self, index: usize) -> Option<BitRefMut<'_, B, L>> {
+        self.iter_mut().nth(index)
+    }
+
+    /// Return an iterator over mutable references to every bit.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2, 99]);
+    /// for (i, mut bit) in bitvec.iter_mut().enumerate() {
+    ///     *bit ^= i % 2 == 0;
+    /// }
+    /// assert_eq!(bitvec.count_ones(), 51);
+    /// assert_eq!(bitvec.get(1), Some(true));
+    /// assert_eq!(bitvec.get(2), Some(false));
+    /// ```
+    pub fn iter_mut(&mut self) -> IterMut<'_, B, L> {
+        Self::check_layout();
+        IterMut {
+            elements: self.storage.as_mut_ptr() as *mut B::Element,
+            start: 0,
+            end: self.nbits,
+            _marker: PhantomData,
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use core::ops::{Add, BitAnd, BitOr, BitXor, Not, Sub};
+
+use wide::u64x4;
+
+use crate::BitBlock;
+
+/// A 512-bit block made of two `u64x4`, filling one AVX-512 register.
+///
+/// Example:
+///
+/// ```rust
+/// use bitvec_simd::{u64x4x2, BitVecSimd};
+///
+/// let bitvec = BitVecSimd::<u64x4x2, 8>::ones(1000);
+/// assert_eq!(bitvec.storage_len(), 2);
+/// assert_eq!(bitvec.count_ones(), 1000);
+/// ```
+#[allow(non_camel_case_types)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[repr(C)]
+pub struct u64x4x2([u64x4; 2]);
+
+macro_rules! impl_lanewise_op {
+    ($trait:ident, $fn:ident) => {
+        impl $trait for u64x4x2 {
+            type Output = Self;
+
+            #[inline]
+            fn $fn(self, rhs: Self) -> Self {
+                Self([self.0[0].$fn(rhs.0[0]), self.0[1].$fn(rhs.0[1])])
+            }
+        }
+    };
+}
+
+impl_lanewise_op!(BitAnd, bitand);
+impl_lanewise_op!(BitOr, bitor);
+impl_lanewise_op!(BitXor, bitxor);
+impl_lanewise_op!(Add, add);
+impl_lanewise_op!(Sub, sub);
+
+impl Not for u64x4x2 {
+    type Output = Self;
+
+    #[inline]
+    fn not(self) -> Self {
+        Self([!self.0[0], !self.0[1]])
+    }
+}
+
+impl From<u64> for u64x4x2 {
+    #[inline]
+    fn from(x: u64) -> Self {
+        Self([u64x4::from(x); 2])
+    }
+}
+
+impl From<[u64; 8]> for u64x4x2 {
+    #[inline]
+    fn from(arr: [u64; 8]) -> Self {
+        Self([
+            u64x4::from([arr[0], arr[1], arr[2], arr[3]]),
+            u64x4::from([arr[4], arr[5], arr[6], arr[7]]),
+        ])
+    }
+}
+
+// SAFETY: `repr(C)` array of two `u64x4`, which is `[u64; 4]` without padding
+unsafe impl BitBlock<8> for u64x4x2 {
+    type Element = u64;
+    const BIT_WIDTH: usize = 512;
+    const ELEMENT_BIT_WIDTH: usize = 64;
+    const LANES: usize = 8;
+    const ZERO_ELEMENT: u64 = 0;
+    const ONE_ELEMENT: u64 = 1;
+    const MAX_ELEMENT: u64 = u64::MAX;
+    const ZERO: Self = Self([u64x4::ZERO; 2]);
+    const MAX: Self = Self([u64x4::MAX; 2]);
+
+    #[inline]
+    fn to_array(self) -> [u64; 8] {
+        let [a, b] = self.0.map(u64x4::to_array);
+        [a[0], a[1], a[2], a[3], b[0], b[1], b[2], b[3]]
+    }
+
+    #[inline]
+    fn from_array(arr: [u64; 8]) -> Self {
+        Self::from(arr)
+    }
+
+    #[inline]
+    fn and_inplace(&mut self, rhs: &Self) {
+        *self = *self & *rhs;
+    }
+
+    #[inline]
+    fn or_inplace(&mut self, rhs: &Self) {
+        *self = *self | *rhs;
+    }
+
+    #[inline]
+    fn xor_inplace(&mut self, rhs: &Self) {
+        *self = *self ^ *rhs;
+    }
+
+    #[inline]
+    fn count_ones(self) -> usize {
+        BitBlock::count_ones(self.0[0]) + BitBlock::count_ones(self.0[1])
+    }
+}
@@ -0,0 +1,323 @@
+use std::{
+    ops::{Deref, DerefMut},
+    ptr,
+    sync::atomic::{AtomicPtr, Ordering},
+};
+
+use crate::{cosine_from_counts, dice_from_counts, jaccard_from_counts, BitVec};
+
+type CountFn = unsafe fn(&[u64]) -> usize;
+type PairCountFn = unsafe fn(&[u64], &[u64]) -> usize;
+// (|a|, |b|, |a & b|)
+type SimilarityFn = unsafe fn(&[u64], &[u64]) -> (usize, usize, usize);
+type AssignFn = unsafe fn(&mut [u64], &[u64]);
+
+// hot kernels over the elements of a `BitVec`, pair kernels only see the common prefix
+struct Kernels {
+    name: &'static str,
+    count_ones: CountFn,
+    and_count: PairCountFn,
+    or_count: PairCountFn,
+    xor_count: PairCountFn,
+    andnot_count: PairCountFn,
+    similarity_counts: SimilarityFn,
+    and_assign: AssignFn,
+    or_assign: AssignFn,
+    xor_assign: AssignFn,
+}
+
+// the same portable code compiled with different target features, the compiler vectorizes it
+// for the enabled instruction sets
+macro_rules! impl_kernels {
+    ($name:ident, $label:literal $(, #[$attr:meta])*) => {
+        mod $name {
+            $(#[$attr])*
+            unsafe fn count_ones(a: &[u64]) -> usize {
+                a.iter().map(|x| x.count_ones() as usize).sum()
+            }
+
+            $(#[$attr])*
+            unsafe fn and_count(a: &[u64], b: &[u64]) -> usize {
+                a.iter().zip(b).map(|(x, y)| (x & y).count_ones() as usize).sum()
+            }
+
+            $(#[$attr])*
+            unsafe fn or_count(a: &[u64], b: &[u64]) -> usize {
+                a.iter().zip(b).map(|(x, y)| (x | y).count_ones() as usize).sum()
+            }
+
+            $(#[$attr])*
+            unsafe fn xor_count(a: &[u64], b: &[u64]) -> usize {
+                a.iter().zip(b).map(|(x, y)| (x ^ y).count_ones() as usize).sum()
+            }
+
+            $(#[$attr])*
+            unsafe fn andnot_count(a: &[u64], b: &[u64]) -> usize {
+                a.iter().zip(b).map(|(x, y)| (x & !y).count_ones() as usize).sum()
+            }
+
+            $(#[$attr])*
+            unsafe fn similarity_counts(a: &[u64], b: &[u64]) -> (usize, usize, usize) {
+                a.iter().zip(b).fold((0, 0, 0), |(x, y, and), (a, b)| {
+                    (
+                        x + a.count_ones() as usize,
+                        y + b.count_ones() as usize,
+                        and + (a & b).count_ones() as usize,
+                    )
+                })
+            }
+
+            $(#[$attr])*
+            unsafe fn and_assign(a: &mut [u64], b: &[u64]) {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x &= y);
+            }
+
+            $(#[$attr])*
+            unsafe fn or_assign(a: &mut [u64], b: &[u64]) {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x |= y);
+            }
+
+            $(#[$attr])*
+            unsafe fn xor_assign(a: &mut [u64], b: &[u64]) {
+                a.iter_mut().zip(b).for_each(|(x, y)| *x ^= y);
+            }
+
+            pub(super) static KERNELS: super::Kernels = super::Kernels {
+                name: $label,
+                count_ones,
+                and_count,
+                or_count,
+                xor_count,
+                andnot_count,
+                similarity_counts,
+                and_assign,
+                or_assign,
+                xor_assign,
+            };
+        }
+    };
+}
+
+impl_kernels!(portable, "portable");
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl_kernels!(popcnt, "popcnt", #[target_feature(enable = "popcnt")]);
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+impl_kernels!(avx2, "avx2", #[target_feature(enable = "avx2,popcnt")]);
+
+fn detect() -> &'static Kernels {
+    #[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+    {
+        if is_x86_feature_detected!("avx2") && is_x86_feature_detected!("popcnt") {
+            return &avx2::KERNELS;
+        }
+        if is_x86_feature_detected!("popcnt") {
+            return &popcnt::KERNELS;
+        }
+    }
+    &portable::KERNELS
+}
+
+#[inline]
+fn kernels() -> &'static Kernels {
+    // detection is idempotent, so racing threads at most detect twice
+    static SELECTED: AtomicPtr<Kernels> = AtomicPtr::new(ptr::null_mut());
+    let selected = SELECTED.load(Ordering::Relaxed);
+    if selected.is_null() {
+        let kernels = detect();
+        SELECTED.store(kernels as *const Kernels as *mut Kernels, Ordering::Relaxed);
+        kernels
+    } else {
+        // SAFETY: only pointers to the `KERNELS` statics are stored
+        unsafe { &*selected }
+    }
+}
+
+// the part of `a` beyond the length of `b`
+#[inline]
+fn tail<'a>(a: &'a [u64], b: &[u64]) -> &'a [u64] {
+    &a[a.len().min(b.len())..]
+}
+
+macro_rules! impl_dyn_operation {
+    ($name:ident, $name_inplace:ident, $kernel:ident) => {
+        /// Panics if the lengths of the two bitvecs aren't the same.
+        pub fn $name(&self, other: &Self) -> Self {
+            let mut result = self.clone();
+            result.$name_inplace(other);
+            result
+        }
+
+        /// Panics if the lengths of the two bitvecs aren't the same.
+        pub fn $name_inplace(&mut self, other: &Self) {
+            assert_eq!(self.bitvec.len(), other.bitvec.len());
+            let kernel = kernels().$kernel;
+            // SAFETY: the kernel was selected for the features of the running CPU
+            unsafe {
+                kernel(
+                    &mut self.bitvec.as_mut_elements(),
+                    other.bitvec.as_elements(),
+                )
+            };
+        }
+    };
+}
+
+/// A [`BitVec`] whose hot kernels are dispatched at runtime to the best implementation for the
+/// running CPU, e.g. AVX2 popcount on x86_64 binaries compiled for the baseline target.
+///
+/// The storage layout is the one of [`BitVec`]. Every other method of [`BitVec`] is available
+/// through `Deref`. Only available with the `std` feature, which is needed for CPU feature
+/// detection.
+///
+/// Example:
+///
+/// ```rust
+/// use bitvec_simd::{BitVec, DynBitVec};
+///
+/// let bitvec = DynBitVec::new(BitVec::from_slice(&[1, 2, 3, 300]));
+/// let bitvec2 = DynBitVec::new(BitVec::from_slice(&[2, 3, 299, 300]));
+/// assert!(matches!(DynBitVec::backend(), "avx2" | "popcnt" | "portable"));
+/// assert_eq!(bitvec.count_ones(), 4);
+/// assert_eq!(bitvec.and_count(&bitvec2), 3);
+/// assert_eq!(bitvec.xor(&bitvec2).into_inner().to_usizes(), vec![1, 299]);
+/// assert_eq!(bitvec.jaccard(&bitvec2), 0.6);
+/// assert_eq!(bitvec.get(300), Some(true));
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DynBitVec {
+    bitvec: BitVec,
+}
+
+impl DynBitVec {
+    /// Wrap `bitvec`, the kernels are selected on first use.
+    #[inline]
+    pub fn new(bitvec: BitVec) -> Self {
+        Self { bitvec }
+    }
+
+    /// Return the wrapped bitvec.
+    #[inline]
+    pub fn into_inner(self) -> BitVec {
+        self.bitvec
+    }
+
+    /// Name of the kernels selected for the running CPU: `"avx2"`, `"popcnt"` or `"portable"`.
+    pub fn backend() -> &'static str {
+        kernels().name
+    }
+
+    /// Count the number of ones, see [`BitVec::count_ones`].
+    pub fn count_ones(&self) -> usize {
+        // SAFETY: the kernel was selected for the features of the running CPU
+        unsafe { (kernels().count_ones)(self.bitvec.as_elements()) }
+    }
+
+    impl_dyn_operation!(and, and_inplace, and_assign);
+    impl_dyn_operation!(or, or_inplace, or_assign);
+    impl_dyn_operation!(xor, xor_inplace, xor_assign);
+
+    /// Same as [`BitVec::and_count`].
+    pub fn and_count(&self, other: &Self) -> usize {
+        let (a, b) = (self.bitvec.as_elements(), other.bitvec.as_elements());
+        // SAFETY: the kernel was selected for the features of the running CPU
+        unsafe { (kernels().and_count)(a, b) }
+    }
+
+    /// Same as [`BitVec::or_count`].
+    pub fn or_count(&self, other: &Self) -> usize {
+        let (a, b) = (self.bitvec.as_elements(), other.bitvec.as_elements());
+        let k = kernels();
+        // SAFETY: the kernels were selected for the features of the running CPU
+        unsafe { (k.or_count)(a, b) + (k.count_ones)(tail(a, b)) + (k.count_ones)(tail(b, a)) }
+    }
+
+    /// Same as [`BitVec::xor_count`].
+    pub fn xor_count(&self, other: &Self) -> usize {
+        let (a, b) = (self.bitvec.as_elements(), other.bitvec.as_elements());
+        let k = kernels();
+        // SAFETY: the kernels were selected for the features of the running CPU
+        unsafe { (k.xor_count)(a, b) + (k.count_ones)(tail(a, b)) + (k.count_ones)(tail(b, a)) }
+    }
+
+    /// Same as [`BitVec::andnot_count`].
+    pub fn andnot_count(&self, other: &Self) -> usize {
+        let (a, b) = (self.bitvec.as_elements(), other.bitvec.as_elements());
+        let k = kernels();
+        // SAFETY: the kernels were selected for the features of the running CPU
+        unsafe { (k.andnot_count)(a, b) + (k.count_ones)(tail(a, b)) }
+    }
+
+    /// Same as [`BitVec::hamming_distance`].
+    #[inline]
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        self.xor_count(other)
+    }
+
+    // (|self|, |other|, |self & other|)
+    fn similarity_counts(&self, other: &Self) -> (usize, usize, usize) {
+        let (a, b) = (self.bitvec.as_elements(), other.bitvec.as_elements());
+        let k = kernels();
+        // SAFETY: the kernels were selected for the features of the running CPU
+        unsafe {
+            let (x, y, and) = (k.similarity_counts)(a, b);
+            (
+                x + (k.count_ones)(tail(a, b)),
+                y + (k.count_ones)(tail(b, a)),
+                and,
+            )
+        }
+    }
+
+    /// Same as [`BitVec::jaccard`].
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        jaccard_from_counts(x, y, and)
+    }
+
+    /// Same as [`BitVec::tanimoto`].
+    #[inline]
+    pub fn tanimoto(&self, other: &Self) -> f64 {
+        self.jaccard(other)
+    }
+
+    /// Same as [`BitVec::dice`].
+    pub fn dice(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        dice_from_counts(x, y, and)
+    }
+
+    /// Same as [`BitVec::cosine`].
+    #[cfg(feature = "std")]
+    pub fn cosine(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        cosine_from_counts(x, y, and)
+    }
+}
+
+impl Deref for DynBitVec {
+    type Target = BitVec;
+
+    #[inline]
+    fn deref(&self) -> &BitVec {
+        &self.bitvec
+    }
+}
+
+impl DerefMut for DynBitVec {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut BitVec {
+        &mut self.bitvec
+    }
+}
+
+impl From<BitVec> for DynBitVec {
+    fn from(bitvec: BitVec) -> Self {
+        Self::new(bitvec)
+    }
+}
+
+impl From<DynBitVec> for BitVec {
+    fn from(bitvec: DynBitVec) -> Self {
+        bitvec.bitvec
+    }
+}
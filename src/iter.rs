@@ -0,0 +1,178 @@
+use core::iter::FusedIterator;
+
+use crate::{BitBlock, BitBlockElement};
+
+/// Iterator over the indices of set bits in a [`BitVecSimd`](crate::BitVecSimd).
+///
+/// Created by [`BitVecSimd::usizes`](crate::BitVecSimd::usizes).
+///
+/// The iterator keeps one element at each end. When an end runs out of bits it loads the next
+/// non-zero element, skipping blocks which are entirely zero with a single comparison.
+#[derive(Clone)]
+pub struct Ones<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    storage: &'a [B],
+    // next element (counted over the whole storage) to be loaded by the front cursor
+    next_elem: usize,
+    // one past the last element to be loaded by the back cursor
+    end_elem: usize,
+    front_base: usize,
+    front_word: B::Element,
+    back_base: usize,
+    back_word: B::Element,
+    remaining: usize,
+}
+
+impl<'a, B, const L: usize> Ones<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    // `count` must be the number of set bits in `storage`
+    pub(crate) fn new(storage: &'a [B], count: usize) -> Self {
+        Self {
+            storage,
+            next_elem: 0,
+            end_elem: storage.len() * B::LANES,
+            front_base: 0,
+            front_word: B::ZERO_ELEMENT,
+            back_base: 0,
+            back_word: B::ZERO_ELEMENT,
+            remaining: count,
+        }
+    }
+
+    // the set bits at `start..end`, the partial elements at both ends are loaded and masked
+    // up front and the count is taken over the range only
+    pub(crate) fn range(storage: &'a [B], start: usize, end: usize) -> Self {
+        let mut ones = Self::new(storage, 0);
+        let width = B::ELEMENT_BIT_WIDTH;
+        let (first, last) = (start / width, end / width + (end % width != 0) as usize);
+        if start >= end {
+            ones.end_elem = 0;
+            return ones;
+        }
+        let high_mask = |x: B::Element| {
+            if end % width == 0 {
+                x
+            } else {
+                x.clear_high_bits((width - end % width) as u32)
+            }
+        };
+        ones.front_word = ones.element(first).clear_low_bits((start % width) as u32);
+        ones.front_base = first * width;
+        if last - 1 == first {
+            ones.front_word = high_mask(ones.front_word);
+        } else {
+            ones.back_word = high_mask(ones.element(last - 1));
+            ones.back_base = (last - 1) * width;
+        }
+        ones.next_elem = first + 1;
+        ones.end_elem = (last - 1).max(first + 1);
+        ones.remaining = ones.front_word.count_ones() as usize
+            + ones.back_word.count_ones() as usize
+            + (ones.next_elem..ones.end_elem)
+                .map(|i| ones.element(i).count_ones() as usize)
+                .sum::<usize>();
+        ones
+    }
+
+    #[inline]
+    fn element(&self, index: usize) -> B::Element {
+        self.storage[index / B::LANES].to_array()[index % B::LANES]
+    }
+}
+
+impl<'a, B, const L: usize> Iterator for Ones<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Item = usize;
+
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if self.front_word != B::ZERO_ELEMENT {
+                let offset = self.front_word.trailing_zeros() as usize;
+                // clear the lowest set bit
+                self.front_word &= self.front_word - B::ONE_ELEMENT;
+                self.remaining -= 1;
+                return Some(self.front_base + offset);
+            }
+            if self.next_elem < self.end_elem {
+                if self.next_elem % B::LANES == 0 {
+                    while self.next_elem + B::LANES <= self.end_elem
+                        && self.storage[self.next_elem / B::LANES] == B::ZERO
+                    {
+                        self.next_elem += B::LANES;
+                    }
+                    if self.next_elem >= self.end_elem {
+                        continue;
+                    }
+                }
+                self.front_word = self.element(self.next_elem);
+                self.front_base = self.next_elem * B::ELEMENT_BIT_WIDTH;
+                self.next_elem += 1;
+            } else if self.back_word != B::ZERO_ELEMENT {
+                // both cursors meet, take over what is left at the back
+                self.front_word = self.back_word;
+                self.front_base = self.back_base;
+                self.back_word = B::ZERO_ELEMENT;
+            } else {
+                return None;
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, B, const L: usize> DoubleEndedIterator for Ones<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn next_back(&mut self) -> Option<usize> {
+        loop {
+            if self.back_word != B::ZERO_ELEMENT {
+                let offset = B::ELEMENT_BIT_WIDTH - 1 - self.back_word.leading_zeros() as usize;
+                // clear the highest set bit
+                self.back_word &= !B::ONE_ELEMENT.wrapping_shl(offset as u32);
+                self.remaining -= 1;
+                return Some(self.back_base + offset);
+            }
+            if self.next_elem < self.end_elem {
+                if self.end_elem % B::LANES == 0 {
+                    while self.next_elem + B::LANES <= self.end_elem
+                        && self.storage[self.end_elem / B::LANES - 1] == B::ZERO
+                    {
+                        self.end_elem -= B::LANES;
+                    }
+                    if self.next_elem >= self.end_elem {
+                        continue;
+                    }
+                }
+                self.end_elem -= 1;
+                self.back_word = self.element(self.end_elem);
+                self.back_base = self.end_elem * B::ELEMENT_BIT_WIDTH;
+            } else if self.front_word != B::ZERO_ELEMENT {
+                self.back_word = self.front_word;
+                self.back_base = self.front_base;
+                self.front_word = B::ZERO_ELEMENT;
+            } else {
+                return None;
+            }
+        }
+    }
+}
+
+impl<'a, B, const L: usize> ExactSizeIterator for Ones<'a, B, L> where B: BitBlock<L> {}
+
+impl<'a, B, const L: usize> FusedIterator for Ones<'a, B, L> where B: BitBlock<L> {}
@@ -47,8 +47,8 @@
 //! // ofcourse you can just use bit-and operator on bitvecs, it will also consumes the inputs.
 //! assert_eq!(bitvec3, bitvec4);
 //! // A bitvec can also be constructed from a collection of bool, or a colluction of integer:
-//! let bitvec: BitVec = (0 .. 10).map(|x| x%2 == 0).into();
-//! let bitvec2: BitVec = (0 .. 10).map(|x| x%3 == 0).into();
+//! let bitvec: BitVec = (0 .. 10).map(|x| x%2 == 0).collect();
+//! let bitvec2: BitVec = (0 .. 10).map(|x| x%3 == 0).collect();
 //! let bitvec3 = BitVec::from_bool_iterator((0..10).map(|x| x%6 == 0));
 //! assert_eq!(bitvec & bitvec2, bitvec3)
 //! ```
@@ -71,20 +71,44 @@ extern crate alloc;
 use alloc::vec::Vec;
 
 use core::{
+    cmp::{self, Ordering},
     fmt,
+    hash::{Hash, Hasher},
+    mem,
     ops::{
-        Add, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Index, Not, Shl, Shr,
-        Sub,
+        Add, BitAnd, BitAndAssign, BitOr, BitOrAssign, BitXor, BitXorAssign, Bound, Index, Not,
+        RangeBounds, Shl, ShlAssign, Shr, ShrAssign, Sub, SubAssign,
     },
 };
 
 use wide::*;
 
+mod binary;
+mod bit_ref;
+mod block;
+#[cfg(feature = "std")]
+mod dispatch;
+mod iter;
+mod rank_select;
+mod raw;
+mod roaring;
 #[cfg(feature = "use_serde")]
-use core::{cmp, marker::PhantomData};
+pub mod serde_compressed;
+mod slice;
+
+pub use binary::DecodeError;
+pub use bit_ref::{BitRefMut, IterMut};
+pub use block::u64x4x2;
+#[cfg(feature = "std")]
+pub use dispatch::DynBitVec;
+pub use iter::Ones;
+pub use rank_select::RankSelect;
+pub use raw::ElementsMut;
+pub use slice::{BitSlice, BitSliceMut, Chunks, ChunksExact};
+
 #[cfg(feature = "use_serde")]
 use serde::{
-    de::{DeserializeOwned, Deserializer, SeqAccess, Visitor},
+    de::{DeserializeOwned, Deserializer},
     ser::{SerializeSeq, Serializer},
     Deserialize, Serialize,
 };
@@ -93,7 +117,7 @@ use serde::{
 ///
 /// see the module's document for examples and details.
 ///
-#[cfg_attr(feature = "use_serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "use_serde", derive(Serialize))]
 #[derive(Debug, Clone)]
 #[repr(C)]
 pub struct BitVecSimd<B, const L: usize>
@@ -102,17 +126,56 @@ where
 {
     // internal representation of bitvec
     #[cfg_attr(feature = "use_serde", serde(serialize_with = "serialize"))]
-    #[cfg_attr(feature = "use_serde", serde(deserialize_with = "deserialize"))]
     storage: Vec<B>,
     // actual number of bits exists in storage
     nbits: usize,
 }
 
+/// Error returned by fallible binary operations when the lengths of two bitvecs differ.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LengthMismatch {
+    /// Length of the left-hand side.
+    pub left: usize,
+    /// Length of the right-hand side.
+    pub right: usize,
+}
+
+impl fmt::Display for LengthMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "length mismatch: left has {} bits, right has {} bits",
+            self.left, self.right
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for LengthMismatch {}
+
+/// How a binary operation treats two bitvecs of different lengths.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LengthPolicy {
+    /// The shorter bitvec is extended with zeros, the result has the longer length.
+    ZeroExtend,
+    /// The longer bitvec is truncated, the result has the shorter length.
+    Truncate,
+}
+
 /// Proc macro can not export BitVec
 /// macro_rules! can not concat ident
 /// so we use name, name_2, etc for function names
 macro_rules! impl_operation {
-    ($name:ident, $name_cloned:ident, $name_inplace:ident, $op:tt) => {
+    (
+        $name:ident,
+        $name_cloned:ident,
+        $name_inplace:ident,
+        $name_try:ident,
+        $name_try_inplace:ident,
+        $name_with:ident,
+        $name_inplace_with:ident,
+        $op:tt
+    ) => {
         /// Panics if the lengths of the two bitsets aren't the same.
         pub fn $name(self, other: Self) -> Self {
             assert_eq!(self.nbits, other.nbits);
@@ -147,6 +210,43 @@ macro_rules! impl_operation {
             assert_eq!(self.nbits, other.nbits);
             self.storage.iter_mut().zip(other.storage.iter()).for_each(|(a, b)| a.$name_inplace(b));
         }
+        /// Returns `Err(LengthMismatch)` instead of panicking if the lengths of the two bitsets
+        /// aren't the same.
+        pub fn $name_try(&self, other: &Self) -> Result<Self, LengthMismatch> {
+            self.check_len(other)?;
+            Ok(self.$name_cloned(other))
+        }
+        /// Returns `Err(LengthMismatch)` and leaves `self` untouched if the lengths of the two
+        /// bitsets aren't the same.
+        pub fn $name_try_inplace(&mut self, other: &Self) -> Result<(), LengthMismatch> {
+            self.check_len(other)?;
+            self.$name_inplace(other);
+            Ok(())
+        }
+        /// Bitsets of different lengths are combined according to `policy`.
+        pub fn $name_with(&self, other: &Self, policy: LengthPolicy) -> Self {
+            let mut bitvec = self.clone();
+            bitvec.$name_inplace_with(other, policy);
+            bitvec
+        }
+        /// Bitsets of different lengths are combined according to `policy`, `self` is resized to
+        /// the length of the result.
+        pub fn $name_inplace_with(&mut self, other: &Self, policy: LengthPolicy) {
+            match policy {
+                LengthPolicy::ZeroExtend if other.nbits > self.nbits => {
+                    self.resize(other.nbits, false)
+                }
+                LengthPolicy::Truncate if other.nbits < self.nbits => {
+                    self.resize(other.nbits, false)
+                }
+                _ => {}
+            }
+            let len = other.storage.len();
+            self.storage.iter_mut().zip(other.storage.iter()).for_each(|(a, b)| a.$name_inplace(b));
+            self.storage.iter_mut().skip(len).for_each(|a| a.$name_inplace(&B::ZERO));
+            // bits of `other` beyond `self.nbits` must not leak into the result
+            self.clear_tail();
+        }
     };
 }
 
@@ -197,7 +297,7 @@ where
     /// assert_eq!(bitvec.len(), 10);
     /// ```
     pub fn zeros(nbits: usize) -> Self {
-        let len = (nbits + B::BIT_WIDTH - 1) / B::BIT_WIDTH;
+        let len = nbits / B::BIT_WIDTH + (nbits % B::BIT_WIDTH != 0) as usize;
         let storage = (0..len).map(|_| B::ZERO).collect();
         Self { storage, nbits }
     }
@@ -216,17 +316,16 @@ where
         let mut storage = (0..len).map(|_| B::MAX).collect::<Vec<_>>();
         if bytes > 0 || bits > 0 {
             let mut arr = B::MAX.to_array();
-            arr[bytes] = B::MAX_ELEMENT.clear_high_bits((B::ELEMENT_BIT_WIDTH - bits) as u32);
-            for a in arr.iter_mut().take(B::LANES).skip(bytes + 1) {
-                *a = B::ZERO_ELEMENT;
-            }
-            storage.push(B::from(arr));
+            Self::clear_arr_high_bits(&mut arr, bytes, bits);
+            storage.push(B::from_array(arr));
         }
         Self { storage, nbits }
     }
 
     /// Create a bitvec from an Iterator of bool.
     ///
+    /// Bitvecs can also be collected from bools, or extended by them.
+    ///
     /// Example:
     ///
     /// ```rust
@@ -242,30 +341,67 @@ where
     /// assert_eq!(bitvec.get(50), Some(false));
     /// assert_eq!(bitvec.get(999), Some(false));
     /// assert_eq!(<BitVec as Into<Vec<bool>>>::into(bitvec), (0..1000).map(|x| x<50).collect::<Vec<bool>>());
+    ///
+    /// let mut bitvec: BitVec = [true, false].into_iter().collect();
+    /// bitvec.extend(&[false, true]);
+    /// assert_eq!(bitvec.len(), 4);
+    /// assert_eq!(bitvec.to_usizes(), vec![0, 3]);
     /// ```
     pub fn from_bool_iterator<I: Iterator<Item = bool>>(i: I) -> Self {
-        // FIXME: any better implementation?
-        let mut storage = Vec::new();
-        let mut current_slice = B::ZERO.to_array();
-        let mut nbits = 0;
-        for b in i {
-            if b {
-                current_slice[nbits % B::BIT_WIDTH / B::ELEMENT_BIT_WIDTH] |=
-                    B::ONE_ELEMENT.wrapping_shl((nbits % B::ELEMENT_BIT_WIDTH) as u32);
+        let mut bitvec = Self::zeros(0);
+        bitvec.extend_bools(i);
+        bitvec
+    }
+
+    // append the bools of `iter`, one element is filled at a time
+    fn extend_bools<I: Iterator<Item = bool>>(&mut self, mut iter: I) {
+        let (lower, _) = iter.size_hint();
+        let blocks = (self.nbits.saturating_add(lower) + B::BIT_WIDTH - 1) / B::BIT_WIDTH;
+        self.storage
+            .reserve(blocks.saturating_sub(self.storage.len()));
+        let (_, mut lane, mut bit) = Self::bit_to_len(self.nbits);
+        // continue from the partial block at the end, if any
+        let mut arr = if lane > 0 || bit > 0 {
+            self.storage.pop().unwrap().to_array()
+        } else {
+            B::ZERO.to_array()
+        };
+        loop {
+            let mut x = arr[lane];
+            let mut end = bit;
+            let mut done = false;
+            while end < B::ELEMENT_BIT_WIDTH {
+                match iter.next() {
+                    Some(b) => {
+                        let one = if b { B::ONE_ELEMENT } else { B::ZERO_ELEMENT };
+                        x |= one.wrapping_shl(end as u32);
+                        end += 1;
+                    }
+                    None => {
+                        done = true;
+                        break;
+                    }
+                }
             }
-            nbits += 1;
-            if nbits % B::BIT_WIDTH == 0 {
-                storage.push(B::from(current_slice));
-                current_slice = B::ZERO.to_array();
+            arr[lane] = x;
+            self.nbits += end - bit;
+            if done {
+                break;
+            }
+            bit = 0;
+            lane += 1;
+            if lane == B::LANES {
+                self.storage.push(B::from_array(arr));
+                arr = B::ZERO.to_array();
+                lane = 0;
             }
         }
-        if nbits % B::BIT_WIDTH > 0 {
-            storage.push(B::from(current_slice));
+        if self.nbits % B::BIT_WIDTH > 0 {
+            self.storage.push(B::from_array(arr));
         }
-        Self { storage, nbits }
     }
 
-    /// Initialize from a set of integers.
+    /// Initialize from a set of integers, the length is one past the largest integer.
     ///
     /// Example:
     ///
@@ -274,13 +410,121 @@ where
     ///
     /// let bitvec = BitVec::from_slice(&[0,5,9]);
     /// assert_eq!(<BitVec as Into<Vec<bool>>>::into(bitvec), vec![true, false, false, false, false, true, false, false, false, true]);
+    ///
+    /// let bitvec = BitVec::from_slice(&[3, 3, 1, 3]);
+    /// assert_eq!(bitvec.len(), 4);
+    /// assert_eq!(bitvec.to_usizes(), vec![1, 3]);
     /// ```
     pub fn from_slice(slice: &[usize]) -> Self {
-        let mut bv = BitVecSimd::zeros(slice.len());
-        for i in slice {
-            bv.set(*i, true);
+        let nbits = slice.iter().max().map_or(0, |max| max + 1);
+        Self::from_indices(slice.iter().copied(), nbits)
+    }
+
+    /// Create a bitvec of `nbits` bits with the bits at `indices` set.
+    ///
+    /// Indices may be unsorted or repeated. Strictly increasing indices can take the faster
+    /// [`BitVecSimd::from_sorted_indices`].
+    /// Panics if an index isn't less than `nbits`.
+    ///
+    /// Bitvecs can also be collected from indices, or extended by them, then the length grows
+    /// to one past the largest index.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_indices((0..100).filter(|x| x % 7 == 0), 1000);
+    /// assert_eq!(bitvec.len(), 1000);
+    /// assert_eq!(bitvec.count_ones(), 15);
+    ///
+    /// let mut bitvec: BitVec = vec![5usize, 2, 9].into_iter().collect();
+    /// assert_eq!(bitvec.len(), 10);
+    /// bitvec.extend(vec![3usize, 20]);
+    /// assert_eq!(bitvec.len(), 21);
+    /// assert_eq!(bitvec.to_usizes(), vec![2, 3, 5, 9, 20]);
+    /// ```
+    pub fn from_indices<I: IntoIterator<Item = usize>>(indices: I, nbits: usize) -> Self {
+        let mut bitvec = Self::zeros(nbits);
+        bitvec.set_indices(indices.into_iter(), false);
+        bitvec
+    }
+
+    /// Create a bitvec of `nbits` bits with the bits at `indices` set, where `indices` are
+    /// strictly increasing.
+    ///
+    /// This is a faster [`BitVecSimd::from_indices`]: every element is written once, in order.
+    /// Unsorted or repeated indices fail a debug assertion, and give an unspecified bitvec in
+    /// release builds.
+    /// Panics if an index isn't less than `nbits`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_sorted_indices((0..100).filter(|x| x % 7 == 0), 1000);
+    /// assert_eq!(bitvec, BitVec::from_indices((0..100).filter(|x| x % 7 == 0), 1000));
+    /// assert_eq!(bitvec.count_ones(), 15);
+    /// ```
+    pub fn from_sorted_indices<I: IntoIterator<Item = usize>>(indices: I, nbits: usize) -> Self {
+        let mut bitvec = Self::zeros(nbits);
+        let elements = bitvec.elements_mut();
+        let mut prev = None;
+        let mut current = 0;
+        let mut x = B::ZERO_ELEMENT;
+        for index in indices {
+            debug_assert!(prev < Some(index), "indices must be strictly increasing");
+            prev = Some(index);
+            assert!(
+                index < nbits,
+                "index {} out of bounds for length {}",
+                index,
+                nbits
+            );
+            let e = index / B::ELEMENT_BIT_WIDTH;
+            if e != current {
+                elements[current] = x;
+                current = e;
+                x = B::ZERO_ELEMENT;
+            }
+            x |= B::ONE_ELEMENT.wrapping_shl((index % B::ELEMENT_BIT_WIDTH) as u32);
+        }
+        if nbits > 0 {
+            elements[current] = x;
+        }
+        bitvec
+    }
+
+    // set the bits at `indices`, indices in a row falling in the same element are combined
+    // before writing it. Panics on an index >= len unless `grow`, which grows the length to
+    // one past it.
+    fn set_indices<I: Iterator<Item = usize>>(&mut self, indices: I, grow: bool) {
+        let mut current = None;
+        let mut x = B::ZERO_ELEMENT;
+        for index in indices {
+            if index >= self.nbits {
+                assert!(
+                    grow,
+                    "index {} out of bounds for length {}",
+                    index, self.nbits
+                );
+                // the storage grows geometrically, like `set`
+                self.resize(index + 1, false);
+            }
+            let e = index / B::ELEMENT_BIT_WIDTH;
+            if current != Some(e) {
+                if let Some(c) = current {
+                    self.elements_mut()[c] |= x;
+                }
+                current = Some(e);
+                x = B::ZERO_ELEMENT;
+            }
+            x |= B::ONE_ELEMENT.wrapping_shl((index % B::ELEMENT_BIT_WIDTH) as u32);
+        }
+        if let Some(c) = current {
+            self.elements_mut()[c] |= x;
         }
-        bv
     }
 
     /// Initialize from a E slice.
@@ -315,7 +559,7 @@ where
             if storage.len() == i && (bytes > 0 || bits > 0) {
                 Self::clear_arr_high_bits(&mut arr, bytes, bits);
             }
-            storage.push(B::from(arr));
+            storage.push(B::from_array(arr));
         }
 
         Self { storage, nbits }
@@ -362,7 +606,7 @@ where
             if storage.len() == i && (bytes > 0 || bits > 0) {
                 Self::clear_arr_high_bits(&mut arr, bytes, bits);
             }
-            storage.push(B::from(arr));
+            storage.push(B::from_array(arr));
         }
 
         Self { storage, nbits }
@@ -434,11 +678,17 @@ where
         }
     }
 
+    // make sure bits > nbits are 0
+    fn clear_tail(&mut self) {
+        let (i, bytes, bits) = Self::bit_to_len(self.nbits);
+        self.clear_high_bits(i, bytes, bits);
+    }
+
     fn clear_high_bits(&mut self, i: usize, bytes: usize, bits: usize) {
         if bytes > 0 || bits > 0 {
             let mut arr = self.storage[i].to_array();
             Self::clear_arr_high_bits(&mut arr, bytes, bits);
-            self.storage[i] = B::from(arr);
+            self.storage[i] = B::from_array(arr);
         }
     }
 
@@ -446,7 +696,7 @@ where
         if bytes > 0 || bits > 0 {
             let mut arr = self.storage[i].to_array();
             Self::fill_arr_high_bits(&mut arr, bytes, bits, bytes_max);
-            self.storage[i] = B::from(arr);
+            self.storage[i] = B::from_array(arr);
         }
     }
 
@@ -476,7 +726,7 @@ where
             }
         }
         Self::clear_arr_high_bits(&mut arr, bytes, bits);
-        self.storage[i] = B::from(arr);
+        self.storage[i] = B::from_array(arr);
     }
 
     /// Resize this bitvec to `nbits` in-place.
@@ -515,7 +765,8 @@ where
     }
 
     /// Shink this bitvec to new length in-place.
-    /// Panics if new length is greater than original.
+    /// Panics if new length is greater than original, see [`BitVecSimd::truncate`] for a
+    /// version which doesn't.
     ///
     /// Example:
     ///
@@ -562,28 +813,94 @@ where
         let (i, bytes, bits) = Self::bit_to_len(index);
         let mut arr = self.storage[i].to_array();
         arr[bytes] = Self::set_bit(flag, arr[bytes], bits as u32);
-        self.storage[i] = B::from(arr);
+        self.storage[i] = B::from_array(arr);
     }
 
-    /// Copy content which ptr points to bitvec storage
-    /// Highly unsafe
-    pub unsafe fn set_raw_copy(&mut self, ptr: *mut B, buffer_len: usize, nbits: usize) {
-        let new_len = (nbits + B::BIT_WIDTH - 1) / B::BIT_WIDTH;
-        assert!(new_len <= buffer_len);
-
-        if new_len > self.len() {
-            self.storage
-                .extend((0..new_len - self.storage.len()).map(move |_| B::ZERO));
+    // truncate `storage` to the blocks needed by `nbits` and clear the bits beyond
+    fn fit_storage(mut storage: Vec<B>, nbits: usize) -> Vec<B> {
+        let (i, bytes, bits) = Self::bit_to_len(nbits);
+        let len = if bytes > 0 || bits > 0 { i + 1 } else { i };
+        assert!(
+            len <= storage.len(),
+            "storage of {} blocks is too short for {} bits",
+            storage.len(),
+            nbits
+        );
+        storage.truncate(len);
+        if bytes > 0 || bits > 0 {
+            let mut arr = storage[i].to_array();
+            Self::clear_arr_high_bits(&mut arr, bytes, bits);
+            storage[i] = B::from_array(arr);
         }
+        storage
+    }
 
-        for i in 0..(new_len as isize) {
-            self.storage[i as usize] = *ptr.offset(i);
+    /// Create a bitvec of `nbits` bits taking ownership of `storage`.
+    ///
+    /// Blocks beyond the ones needed by `nbits` are dropped and bits at or above `nbits` are
+    /// cleared. Panics if `storage` holds fewer than `nbits` bits.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    /// use wide::u64x4;
+    ///
+    /// let bitvec = BitVec::from_storage(vec![u64x4::from([0b1011, 0, 0, 0]); 2], 2);
+    /// assert_eq!(bitvec.len(), 2);
+    /// assert_eq!(bitvec.storage_len(), 1);
+    /// assert_eq!(bitvec.to_usizes(), vec![0, 1]);
+    /// ```
+    pub fn from_storage(storage: Vec<B>, nbits: usize) -> Self {
+        Self {
+            storage: Self::fit_storage(storage, nbits),
+            nbits,
         }
+    }
+
+    /// Consume this bitvec and return its storage.
+    ///
+    /// The storage holds `ceil(len / BIT_WIDTH)` blocks and every bit at or above `len` is zero.
+    pub fn into_storage(self) -> Vec<B> {
+        self.storage
+    }
+
+    /// Replace the storage of this bitvec with `storage` holding `nbits` bits, and return the old
+    /// storage.
+    ///
+    /// `storage` is handled the same as in [`BitVecSimd::from_storage`].
+    /// Panics if `storage` holds fewer than `nbits` bits.
+    pub fn replace_storage(&mut self, storage: Vec<B>, nbits: usize) -> Vec<B> {
+        let storage = Self::fit_storage(storage, nbits);
         self.nbits = nbits;
+        mem::replace(&mut self.storage, storage)
+    }
+
+    /// Copy `ceil(nbits / BIT_WIDTH)` blocks from `ptr` into the storage of this bitvec.
+    /// Bits at or above `nbits` are cleared, see [`BitVecSimd::replace_storage`].
+    ///
+    /// Prefer [`BitVecSimd::replace_storage`] with a copied `Vec`.
+    ///
+    /// # Safety
+    ///
+    /// `ptr` must be valid for reads of `buffer_len` blocks.
+    pub unsafe fn set_raw_copy(&mut self, ptr: *mut B, buffer_len: usize, nbits: usize) {
+        debug_assert!(!ptr.is_null());
+        let new_len = (nbits + B::BIT_WIDTH - 1) / B::BIT_WIDTH;
+        assert!(new_len <= buffer_len);
+        let storage = core::slice::from_raw_parts(ptr, new_len).to_vec();
+        self.replace_storage(storage, nbits);
     }
 
-    /// Directly set storage to ptr
-    /// Highly unsafe
+    /// Directly set storage to ptr.
+    /// Bits at or above `nbits` are cleared, see [`BitVecSimd::replace_storage`].
+    ///
+    /// Prefer [`BitVecSimd::replace_storage`] with an owned `Vec`.
+    ///
+    /// # Safety
+    ///
+    /// All the requirements of [`Vec::from_raw_parts`] must hold for `ptr`, `buffer_len` and
+    /// `capacity`.
     pub unsafe fn set_raw(
         &mut self,
         ptr: *mut B,
@@ -591,8 +908,9 @@ where
         capacity: usize,
         nbits: usize,
     ) {
-        self.storage = Vec::from_raw_parts(ptr, buffer_len, capacity);
-        self.nbits = nbits;
+        debug_assert!(!ptr.is_null());
+        debug_assert!(buffer_len <= capacity);
+        self.replace_storage(Vec::from_raw_parts(ptr, buffer_len, capacity), nbits);
     }
 
     /// Set all items in bitvec to false
@@ -606,12 +924,9 @@ where
         self.storage.iter_mut().for_each(move |x| *x = B::MAX);
         if bytes > 0 || bits > 0 {
             let mut arr = B::MAX.to_array();
-            arr[bytes] = B::MAX_ELEMENT.clear_high_bits((B::ELEMENT_BIT_WIDTH - bits) as u32);
-            for a in arr.iter_mut().take(B::LANES).skip(bytes + 1) {
-                *a = B::ZERO_ELEMENT;
-            }
+            Self::clear_arr_high_bits(&mut arr, bytes, bits);
             // unwrap here is safe since bytes > 0 || bits > 0 => self.nbits > 0
-            *(self.storage.last_mut().unwrap()) = B::from(arr);
+            *(self.storage.last_mut().unwrap()) = B::from_array(arr);
         }
     }
 
@@ -623,6 +938,105 @@ where
         }
     }
 
+    // apply `f(block, mask)` on every block overlapping `start..end`, where `mask` has exactly
+    // the bits inside of `start..end` set. The bitvec is expanded to `end` first.
+    fn apply_range_mask<F: Fn(&mut B, &B)>(&mut self, start: usize, end: usize, f: F) {
+        if start >= end {
+            return;
+        }
+        if end > self.nbits {
+            self.resize(end, false);
+        }
+        let (start_i, start_bytes, start_bits) = Self::bit_to_len(start);
+        let (end_i, end_bytes, end_bits) = Self::bit_to_len(end);
+        let last = if end_bytes > 0 || end_bits > 0 {
+            end_i
+        } else {
+            end_i - 1
+        };
+        for (i, x) in self
+            .storage
+            .iter_mut()
+            .enumerate()
+            .take(last + 1)
+            .skip(start_i)
+        {
+            if i == start_i || i == end_i {
+                let mut arr = if i == start_i {
+                    let mut arr = B::ZERO.to_array();
+                    Self::fill_arr_high_bits(&mut arr, start_bytes, start_bits, B::LANES);
+                    arr
+                } else {
+                    B::MAX.to_array()
+                };
+                if i == end_i {
+                    Self::clear_arr_high_bits(&mut arr, end_bytes, end_bits);
+                }
+                f(x, &B::from_array(arr));
+            } else {
+                f(x, &B::MAX);
+            }
+        }
+    }
+
+    /// Set every bit in `range` to `flag`.
+    /// If the range ends beyond `self.len()`, the bitvec will be expanded to the end of the range,
+    /// just like `set`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::zeros(10);
+    /// bitvec.set_range(2..5, true);
+    /// assert_eq!(bitvec.to_usizes(), vec![2, 3, 4]);
+    ///
+    /// let mut bitvec = BitVec::zeros(10);
+    /// bitvec.set_range(8..=1000, true);
+    /// assert_eq!(bitvec.len(), 1001);
+    /// assert_eq!(bitvec.count_ones(), 993);
+    /// bitvec.set_range(..500, false);
+    /// assert_eq!(bitvec.count_ones(), 501);
+    /// ```
+    pub fn set_range<R: RangeBounds<usize>>(&mut self, range: R, flag: bool) {
+        let (start, end) = range_to_bounds_unchecked(range, self.nbits);
+        if flag {
+            self.apply_range_mask(start, end, |x, mask| x.or_inplace(mask));
+        } else {
+            self.apply_range_mask(start, end, |x, mask| *x = x.and_not(*mask));
+        }
+    }
+
+    /// Set every bit in `range` to true, same as `set_range(range, true)`.
+    pub fn fill_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.set_range(range, true);
+    }
+
+    /// Set every bit in `range` to false, same as `set_range(range, false)`.
+    pub fn clear_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        self.set_range(range, false);
+    }
+
+    /// Flip every bit in `range`.
+    /// If the range ends beyond `self.len()`, the bitvec will be expanded to the end of the range,
+    /// so the new bits become true.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 3]);
+    /// bitvec.toggle_range(1..6);
+    /// assert_eq!(bitvec.len(), 6);
+    /// assert_eq!(bitvec.to_usizes(), vec![2, 4, 5]);
+    /// ```
+    pub fn toggle_range<R: RangeBounds<usize>>(&mut self, range: R) {
+        let (start, end) = range_to_bounds_unchecked(range, self.nbits);
+        self.apply_range_mask(start, end, |x, mask| x.xor_inplace(mask));
+    }
+
     /// Check if `index` exists in current set.
     ///
     /// * If exists, return `Some(true)`
@@ -634,7 +1048,7 @@ where
     /// ```rust
     /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec : BitVec = (0 .. 15).map(|x| x%3 == 0).into();
+    /// let bitvec : BitVec = (0 .. 15).map(|x| x%3 == 0).collect();
     /// assert_eq!(bitvec.get(3), Some(true));
     /// assert_eq!(bitvec.get(5), Some(false));
     /// assert_eq!(bitvec.get(14), Some(false));
@@ -664,7 +1078,7 @@ where
     /// ```rust
     /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec : BitVec = (0 .. 15).map(|x| x%3 == 0).into();
+    /// let bitvec : BitVec = (0 .. 15).map(|x| x%3 == 0).collect();
     /// assert_eq!(bitvec.get_unchecked(3), true);
     /// assert_eq!(bitvec.get_unchecked(5), false);
     /// assert_eq!(bitvec.get_unchecked(14), false);
@@ -679,12 +1093,53 @@ where
         }
     }
 
-    impl_operation!(and, and_cloned, and_inplace, &);
-    impl_operation!(or, or_cloned, or_inplace, |);
-    impl_operation!(xor, xor_cloned, xor_inplace, ^);
+    #[inline]
+    fn check_len(&self, other: &Self) -> Result<(), LengthMismatch> {
+        if self.nbits == other.nbits {
+            Ok(())
+        } else {
+            Err(LengthMismatch {
+                left: self.nbits,
+                right: other.nbits,
+            })
+        }
+    }
+
+    impl_operation!(
+        and,
+        and_cloned,
+        and_inplace,
+        try_and,
+        try_and_inplace,
+        and_with,
+        and_inplace_with,
+        &
+    );
+    impl_operation!(
+        or,
+        or_cloned,
+        or_inplace,
+        try_or,
+        try_or_inplace,
+        or_with,
+        or_inplace_with,
+        |
+    );
+    impl_operation!(
+        xor,
+        xor_cloned,
+        xor_inplace,
+        try_xor,
+        try_xor_inplace,
+        xor_with,
+        xor_inplace_with,
+        ^
+    );
 
     /// Doesn't change the length of `self`, so if `other` contains more bits those will be
     /// ignored.
+    ///
+    /// See `or_inplace_with` for other ways of handling different lengths.
     pub fn or_inplace_mismatched_len(&mut self, other: &Self) {
         self.storage
             .iter_mut()
@@ -712,18 +1167,69 @@ where
     /// ```rust
     /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec: BitVec = (0 .. 5_000).map(|x| x % 2 == 0).into();
-    /// let bitvec2 : BitVec = (0 .. 5_000).map(|x| x % 3 == 0).into();
+    /// let bitvec: BitVec = (0 .. 5_000).map(|x| x % 2 == 0).collect();
+    /// let bitvec2 : BitVec = (0 .. 5_000).map(|x| x % 3 == 0).collect();
     /// assert_eq!(bitvec.difference_cloned(&bitvec2) | bitvec2.difference_cloned(&bitvec), bitvec.xor_cloned(&bitvec2));
-    /// let bitvec3 : BitVec = (0 .. 5_000).map(|x| x % 2 == 0 && x % 3 != 0).into();
+    /// let bitvec3 : BitVec = (0 .. 5_000).map(|x| x % 2 == 0 && x % 3 != 0).collect();
     /// assert_eq!(bitvec.difference(bitvec2), bitvec3);
     /// ```
+    ///
+    /// Panics if the lengths of the two bitsets aren't the same.
     pub fn difference(self, other: Self) -> Self {
-        self.and(other.not())
+        assert_eq!(self.nbits, other.nbits);
+        let storage = self
+            .storage
+            .into_iter()
+            .zip(other.storage)
+            .map(|(a, b)| a.and_not(b))
+            .collect();
+        Self {
+            storage,
+            nbits: self.nbits,
+        }
     }
 
+    /// Same as `difference`, but accepts references and doesn't consume the inputs.
+    ///
+    /// Panics if the lengths of the two bitsets aren't the same.
     pub fn difference_cloned(&self, other: &Self) -> Self {
-        self.and_cloned(&other.not())
+        assert_eq!(self.nbits, other.nbits);
+        let storage = self
+            .storage
+            .iter()
+            .cloned()
+            .zip(other.storage.iter().cloned())
+            .map(|(a, b)| a.and_not(b))
+            .collect();
+        Self {
+            storage,
+            nbits: self.nbits,
+        }
+    }
+
+    /// Remove every element of `other` from `self` in-place.
+    ///
+    /// Panics if the lengths of the two bitsets aren't the same.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec: BitVec = (0 .. 100).map(|x| x % 2 == 0).collect();
+    /// let bitvec2 : BitVec = (0 .. 100).map(|x| x % 3 == 0).collect();
+    /// let bitvec3 : BitVec = (0 .. 100).map(|x| x % 2 == 0 && x % 3 != 0).collect();
+    /// bitvec.difference_inplace(&bitvec2);
+    /// assert_eq!(bitvec, bitvec3);
+    /// bitvec -= &bitvec2;
+    /// assert_eq!(bitvec.count_ones(), 33);
+    /// ```
+    pub fn difference_inplace(&mut self, other: &Self) {
+        assert_eq!(self.nbits, other.nbits);
+        self.storage
+            .iter_mut()
+            .zip(other.storage.iter())
+            .for_each(|(a, b)| *a = a.and_not(*b));
     }
 
     // not should make sure bits > nbits is 0
@@ -733,16 +1239,13 @@ where
     /// after inverse it will contains `0, 2..=4, 6..=999`
     pub fn inverse(&self) -> Self {
         let (i, bytes, bits) = Self::bit_to_len(self.nbits);
-        let mut storage = self.storage.iter().map(|x| !(*x)).collect::<Vec<_>>();
+        let mut storage = self.storage.iter().map(|x| *x ^ B::MAX).collect::<Vec<_>>();
         if bytes > 0 || bits > 0 {
             assert_eq!(storage.len(), i + 1);
             let s: &mut B = &mut storage[i];
             let mut arr = s.to_array();
-            arr[bytes] = arr[bytes].clear_high_bits((B::ELEMENT_BIT_WIDTH - bits) as u32);
-            for a in arr.iter_mut().take(B::LANES).skip(bytes + 1) {
-                *a = B::ZERO_ELEMENT;
-            }
-            *s = arr.into();
+            Self::clear_arr_high_bits(&mut arr, bytes, bits);
+            *s = B::from_array(arr);
         }
 
         Self {
@@ -751,69 +1254,614 @@ where
         }
     }
 
-    /// Count the number of elements existing in this bitvec.
+    // element `index` of the storage seen as a flat array, zero if out of range
+    #[inline]
+    fn element(&self, index: usize) -> <B as BitBlock<L>>::Element {
+        self.as_elements()
+            .get(index)
+            .copied()
+            .unwrap_or(B::ZERO_ELEMENT)
+    }
+
+    // copy bits `start..start + nbits` into a new bitvec starting from bit 0
+    pub(crate) fn extract(&self, start: usize, nbits: usize) -> Self {
+        debug_assert!(start + nbits <= self.nbits);
+        let src = self.as_elements();
+        let offset = start / B::ELEMENT_BIT_WIDTH;
+        let bits = (start % B::ELEMENT_BIT_WIDTH) as u32;
+        let mut bitvec = Self::zeros(nbits);
+        {
+            // bits copied beyond nbits are cleared when `dst` is dropped
+            let mut dst = bitvec.as_mut_elements();
+            let len = dst.len().min(src.len() - offset);
+            for k in 0..len {
+                dst[k] = src[offset + k].wrapping_shr(bits);
+                if bits > 0 && offset + k + 1 < src.len() {
+                    dst[k] |= src[offset + k + 1].wrapping_shl(B::ELEMENT_BIT_WIDTH as u32 - bits);
+                }
+            }
+        }
+        bitvec
+    }
+
+    // overwrite bits `start..start + src.len()` with the content of `src`
+    pub(crate) fn splice_bits(&mut self, start: usize, src: &Self) {
+        self.combine_bits(start, src.as_elements(), 0, src.nbits, |_, y| y);
+    }
+
+    // replace every bit `x` of `start..start + nbits` with the matching bit of `f(x, y)`, where
+    // `y` is the bit of `src` at the same distance from `src_start`, an element at a time
+    pub(crate) fn combine_bits<F>(
+        &mut self,
+        start: usize,
+        src: &[<B as BitBlock<L>>::Element],
+        src_start: usize,
+        nbits: usize,
+        f: F,
+    ) where
+        F: Fn(
+            <B as BitBlock<L>>::Element,
+            <B as BitBlock<L>>::Element,
+        ) -> <B as BitBlock<L>>::Element,
+    {
+        debug_assert!(start + nbits <= self.nbits);
+        let width = B::ELEMENT_BIT_WIDTH as u32;
+        let src_offset = src_start / B::ELEMENT_BIT_WIDTH;
+        let src_bits = (src_start % B::ELEMENT_BIT_WIDTH) as u32;
+        let src_element = |i: usize| src.get(i).copied().unwrap_or(B::ZERO_ELEMENT);
+        let offset = start / B::ELEMENT_BIT_WIDTH;
+        let bits = (start % B::ELEMENT_BIT_WIDTH) as u32;
+        let elements = nbits / B::ELEMENT_BIT_WIDTH + (nbits % B::ELEMENT_BIT_WIDTH != 0) as usize;
+        // only bits inside the range are written, so bits at or above nbits stay clear
+        let dst = self.elements_mut();
+        for k in 0..elements {
+            // the next element of source bits, realigned to bit 0
+            let mut value = src_element(src_offset + k).wrapping_shr(src_bits);
+            if src_bits > 0 {
+                value |= src_element(src_offset + k + 1).wrapping_shl(width - src_bits);
+            }
+            let rest = nbits - k * B::ELEMENT_BIT_WIDTH;
+            let mask = if rest >= B::ELEMENT_BIT_WIDTH {
+                B::MAX_ELEMENT
+            } else {
+                B::MAX_ELEMENT.clear_high_bits(width - rest as u32)
+            };
+            let m = mask.wrapping_shl(bits);
+            let x = &mut dst[offset + k];
+            *x = (*x & !m) | (f(*x, value.wrapping_shl(bits)) & m);
+            let spill = width - bits;
+            if bits > 0 && mask.wrapping_shr(spill) != B::ZERO_ELEMENT {
+                let m = mask.wrapping_shr(spill);
+                let x = &mut dst[offset + k + 1];
+                *x = (*x & !m) | (f(*x, value.wrapping_shr(spill)) & m);
+            }
+        }
+    }
+
+    /// Borrow bits in `range` as a [`BitSlice`].
+    /// Panics if the range is out of bounds.
     ///
     /// Example:
     ///
     /// ```rust
     /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec: BitVec = (0..10_000).map(|x| x%2==0).into();
-    /// assert_eq!(bitvec.count_ones(), 5000);
-    ///
-    /// let bitvec: BitVec = (0..30_000).map(|x| x%3==0).into();
-    /// assert_eq!(bitvec.count_ones(), 10_000);
+    /// let bitvec: BitVec = (0..1000).map(|x| x % 3 == 0).collect();
+    /// let slice = bitvec.slice(100..200);
+    /// assert_eq!(slice.len(), 100);
+    /// assert_eq!(slice.get(2), Some(true));
+    /// assert_eq!(slice.count_ones(), 33);
+    /// assert_eq!(slice.to_bitvec().to_usizes()[..3], [2, 5, 8]);
     /// ```
-    pub fn count_ones(&self) -> usize {
-        self.storage
-            .iter()
-            .map(|x| {
-                x.to_array()
-                    .into_iter()
-                    .map(|a| a.count_ones())
-                    .sum::<u32>()
-            })
-            .sum::<u32>() as usize
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> BitSlice<'_, B, L> {
+        let (start, end) = range_to_bounds(range, self.nbits);
+        BitSlice::new(self, start, end)
     }
 
-    /// Count the number of elements existing in this bitvec, before the specified index.
-    /// Panics if index is invalid.
+    /// Mutably borrow bits in `range` as a [`BitSliceMut`].
+    /// Panics if the range is out of bounds.
     ///
     /// Example:
     ///
     /// ```rust
     /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec: BitVec = (0..10_000).map(|x| x%2==0).into();
-    /// assert_eq!(bitvec.count_ones_before(5000), 2500);
+    /// let mut bitvec = BitVec::zeros(1000);
+    /// let mut slice = bitvec.slice_mut(100..200);
+    /// slice.set(5, true);
+    /// slice.or_inplace(&BitVec::ones(100).slice(..));
+    /// assert_eq!(slice.count_ones(), 100);
+    /// assert_eq!(bitvec.count_ones(), 100);
+    /// assert_eq!(bitvec.get(99), Some(false));
+    /// assert_eq!(bitvec.get(100), Some(true));
+    /// ```
+    pub fn slice_mut<R: RangeBounds<usize>>(&mut self, range: R) -> BitSliceMut<'_, B, L> {
+        let (start, end) = range_to_bounds(range, self.nbits);
+        BitSliceMut::new(self, start, end)
+    }
+
+    /// Return an iterator over [`BitSlice`]s of `size` bits, the last one has the bits left over.
+    /// Panics if size is 0.
     ///
-    /// let bitvec: BitVec = (0..30_000).map(|x| x%3==0).into();
-    /// assert_eq!(bitvec.count_ones_before(10000), 3334);
+    /// Example:
     ///
-    /// let bitvec: BitVec = (0..1).map(|x| true).into();
-    /// assert_eq!(bitvec.count_ones_before(0), 0);
-    /// assert_eq!(bitvec.count_ones_before(1), 1);
+    /// ```rust
+    /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec: BitVec = (0..10).map(|x| true).into();
-    /// for i in 0..10 {
-    ///     assert_eq!(bitvec.count_ones_before(i), i);
-    /// }
+    /// let bitvec = BitVec::from_slice(&[1, 100, 299]);
+    /// let chunks: Vec<_> = bitvec.chunks(100).map(|c| c.to_bitvec().to_usizes()).collect();
+    /// assert_eq!(chunks, vec![vec![1], vec![0], vec![99]]);
+    /// assert_eq!(bitvec.chunks(128).map(|c| c.len()).collect::<Vec<_>>(), vec![128, 128, 44]);
+    /// ```
+    pub fn chunks(&self, size: usize) -> Chunks<'_, B, L> {
+        Chunks::new(self, size)
+    }
+
+    /// Return an iterator over [`BitSlice`]s of exactly `size` bits, the bits left over are
+    /// available from [`ChunksExact::remainder`].
+    /// Panics if size is 0.
     ///
-    /// let bitvec: BitVec = (0..10).map(|x| x==0).into();
-    /// assert_eq!(bitvec.count_ones_before(0), 0);
-    /// for i in 1..10 {
-    ///     assert_eq!(bitvec.count_ones_before(i), 1);
-    /// }
+    /// Example:
     ///
-    /// let bitvec: BitVec = (0..31).map(|x| true).into();
-    /// assert_eq!(bitvec.count_ones_before(0), 0);
-    /// for i in 1..31 {
-    ///     assert_eq!(bitvec.count_ones_before(i), i);
-    /// }
+    /// ```rust
+    /// use bitvec_simd::BitVec;
     ///
-    /// let bitvec: BitVec = (0..32).map(|x| true).into();
-    /// assert_eq!(bitvec.count_ones_before(0), 0);
-    /// for i in 1..32 {
+    /// let bitvec = BitVec::from_slice(&[1, 100, 299]);
+    /// let chunks = bitvec.chunks_exact(128);
+    /// assert_eq!(chunks.remainder().len(), 44);
+    /// assert_eq!(chunks.remainder().usizes().collect::<Vec<_>>(), vec![43]);
+    /// assert_eq!(chunks.map(|c| c.count_ones()).collect::<Vec<_>>(), vec![2, 0]);
+    /// ```
+    pub fn chunks_exact(&self, size: usize) -> ChunksExact<'_, B, L> {
+        ChunksExact::new(self, size)
+    }
+
+    /// Shift all bits towards higher indices by `n` in-place, keeping the length.
+    ///
+    /// Bit `i` moves to `i + n`, bits moved beyond `len` are dropped and `0..n` is filled with 0.
+    /// This is the same as `bitvec <<= n`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[0, 2, 9]);
+    /// bitvec.shl_inplace(3);
+    /// assert_eq!(bitvec.len(), 10);
+    /// assert_eq!(bitvec.to_usizes(), vec![3, 5]);
+    /// ```
+    pub fn shl_inplace(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        if n >= self.nbits {
+            self.set_all_false();
+            return;
+        }
+        let words = n / B::ELEMENT_BIT_WIDTH;
+        let bits = (n % B::ELEMENT_BIT_WIDTH) as u32;
+        // bits shifted beyond nbits are cleared when `elements` is dropped
+        let mut elements = self.as_mut_elements();
+        // walk downwards so that every source element is read before being overwritten
+        for i in (0..elements.len()).rev() {
+            elements[i] = if i < words {
+                B::ZERO_ELEMENT
+            } else if bits > 0 && i > words {
+                elements[i - words].wrapping_shl(bits)
+                    | elements[i - words - 1].wrapping_shr(B::ELEMENT_BIT_WIDTH as u32 - bits)
+            } else {
+                elements[i - words].wrapping_shl(bits)
+            };
+        }
+    }
+
+    /// Shift all bits towards lower indices by `n` in-place, keeping the length.
+    ///
+    /// Bit `i` moves to `i - n`, bits moved below 0 are dropped and `len - n..len` is filled
+    /// with 0. This is the same as `bitvec >>= n`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[0, 2, 9]);
+    /// bitvec.shr_inplace(2);
+    /// assert_eq!(bitvec.len(), 10);
+    /// assert_eq!(bitvec.to_usizes(), vec![0, 7]);
+    /// ```
+    pub fn shr_inplace(&mut self, n: usize) {
+        if n == 0 {
+            return;
+        }
+        if n >= self.nbits {
+            self.set_all_false();
+            return;
+        }
+        let words = n / B::ELEMENT_BIT_WIDTH;
+        let bits = (n % B::ELEMENT_BIT_WIDTH) as u32;
+        let mut elements = self.as_mut_elements();
+        let len = elements.len();
+        // walk upwards so that every source element is read before being overwritten,
+        // bits > nbits are 0 so nothing needs to be cleared afterwards
+        for i in 0..len {
+            let src = i + words;
+            elements[i] = if src >= len {
+                B::ZERO_ELEMENT
+            } else if bits > 0 && src + 1 < len {
+                elements[src].wrapping_shr(bits)
+                    | elements[src + 1].wrapping_shl(B::ELEMENT_BIT_WIDTH as u32 - bits)
+            } else {
+                elements[src].wrapping_shr(bits)
+            };
+        }
+    }
+
+    /// Shift all bits towards higher indices by `n` and return a new bitvec with the same length.
+    pub fn shl_cloned(&self, n: usize) -> Self {
+        let mut bitvec = self.clone();
+        bitvec.shl_inplace(n);
+        bitvec
+    }
+
+    /// Shift all bits towards lower indices by `n` and return a new bitvec with the same length.
+    pub fn shr_cloned(&self, n: usize) -> Self {
+        let mut bitvec = self.clone();
+        bitvec.shr_inplace(n);
+        bitvec
+    }
+
+    /// Shift all bits towards higher indices by `n` in-place, growing the length by `n` so that no
+    /// bit is dropped.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[0, 2, 9]);
+    /// bitvec.shl_grow(300);
+    /// assert_eq!(bitvec.len(), 310);
+    /// assert_eq!(bitvec.to_usizes(), vec![300, 302, 309]);
+    /// ```
+    pub fn shl_grow(&mut self, n: usize) {
+        self.resize(self.nbits + n, false);
+        self.shl_inplace(n);
+    }
+
+    /// Shift all bits towards lower indices by `n` in-place, shrinking the length by `n` (or to 0)
+    /// so that no zero is introduced at the top.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[0, 2, 9]);
+    /// bitvec.shr_shrink(2);
+    /// assert_eq!(bitvec.len(), 8);
+    /// assert_eq!(bitvec.to_usizes(), vec![0, 7]);
+    /// ```
+    pub fn shr_shrink(&mut self, n: usize) {
+        self.shr_inplace(n);
+        self.resize(self.nbits.saturating_sub(n), false);
+    }
+
+    /// Append `flag` after the last bit, growing the length by 1.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::zeros(0);
+    /// bitvec.push(true);
+    /// bitvec.push(false);
+    /// bitvec.push(true);
+    /// assert_eq!(bitvec.len(), 3);
+    /// assert_eq!(bitvec.get(2), Some(true));
+    /// assert_eq!(bitvec.pop(), Some(true));
+    /// assert_eq!(bitvec.pop(), Some(false));
+    /// assert_eq!(bitvec.len(), 1);
+    /// ```
+    pub fn push(&mut self, flag: bool) {
+        if self.nbits % B::BIT_WIDTH == 0 {
+            self.storage.push(B::ZERO);
+        }
+        self.nbits += 1;
+        if flag {
+            self.set(self.nbits - 1, true);
+        }
+    }
+
+    /// Remove the last bit and return it, or `None` if this bitvec is empty.
+    pub fn pop(&mut self) -> Option<bool> {
+        if self.nbits == 0 {
+            return None;
+        }
+        let flag = self.get_unchecked(self.nbits - 1);
+        self.resize(self.nbits - 1, false);
+        Some(flag)
+    }
+
+    // append the bits of `src` after the last bit
+    fn append_bits(&mut self, src: &Self) {
+        let start = self.nbits;
+        if start % B::BIT_WIDTH == 0 {
+            // aligned, the blocks can be copied as they are
+            self.storage.extend_from_slice(&src.storage);
+            self.nbits += src.nbits;
+        } else {
+            self.resize(start + src.nbits, false);
+            self.splice_bits(start, src);
+        }
+    }
+
+    /// Move all bits of `other` after the last bit of this bitvec, leaving `other` empty.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2]);
+    /// let mut bitvec2 = BitVec::from_slice(&[0, 299]);
+    /// bitvec.append(&mut bitvec2);
+    /// assert_eq!(bitvec.len(), 303);
+    /// assert_eq!(bitvec.to_usizes(), vec![1, 2, 3, 302]);
+    /// assert!(bitvec2.is_empty());
+    /// ```
+    pub fn append(&mut self, other: &mut Self) {
+        self.append_bits(other);
+        other.storage.clear();
+        other.nbits = 0;
+    }
+
+    /// Copy the bits of `slice` after the last bit of this bitvec.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2]);
+    /// let bitvec2 = BitVec::from_slice(&[0, 5, 10, 299]);
+    /// bitvec.extend_from_bitslice(&bitvec2.slice(5..11));
+    /// assert_eq!(bitvec.len(), 9);
+    /// assert_eq!(bitvec.to_usizes(), vec![1, 2, 3, 8]);
+    /// ```
+    pub fn extend_from_bitslice(&mut self, slice: &BitSlice<'_, B, L>) {
+        let start = self.nbits;
+        self.resize(start + slice.len(), false);
+        let src = slice.bitvec.as_elements();
+        self.combine_bits(start, src, slice.start, slice.len(), |_, y| y);
+    }
+
+    /// Insert `flag` at `index`, shifting the bits at `index..` towards higher indices by 1.
+    /// Panics if index > len.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2, 300]);
+    /// bitvec.insert(2, true);
+    /// bitvec.insert(0, false);
+    /// assert_eq!(bitvec.len(), 303);
+    /// assert_eq!(bitvec.to_usizes(), vec![2, 3, 4, 302]);
+    /// ```
+    pub fn insert(&mut self, index: usize, flag: bool) {
+        if index > self.nbits {
+            panic!("index out of bounds {} > {}", index, self.nbits);
+        }
+        self.push(false);
+        let (first, end, bit) = self.shift_bounds(index);
+        let top = B::ELEMENT_BIT_WIDTH as u32 - 1;
+        let low = B::ONE_ELEMENT.wrapping_shl(bit) - B::ONE_ELEMENT;
+        // shift `index..` up by 1 in place, the top bit of every element carries into the next
+        // one. The pushed last bit was 0, so nothing is carried out of the last element.
+        let elements = self.elements_mut();
+        let x = elements[first];
+        let mut carry = x.wrapping_shr(top);
+        elements[first] = (x & low) | (x & !low).wrapping_shl(1);
+        if flag {
+            elements[first] |= B::ONE_ELEMENT.wrapping_shl(bit);
+        }
+        for e in &mut elements[first + 1..end] {
+            let x = *e;
+            *e = x.wrapping_shl(1) | carry;
+            carry = x.wrapping_shr(top);
+        }
+    }
+
+    // (element of bit `index`, end of the used elements, bit of `index` in its element)
+    #[inline]
+    fn shift_bounds(&self, index: usize) -> (usize, usize, u32) {
+        let end =
+            self.nbits / B::ELEMENT_BIT_WIDTH + (self.nbits % B::ELEMENT_BIT_WIDTH != 0) as usize;
+        (
+            index / B::ELEMENT_BIT_WIDTH,
+            end,
+            (index % B::ELEMENT_BIT_WIDTH) as u32,
+        )
+    }
+
+    /// Remove the bit at `index` and return it, shifting the bits after it towards lower indices
+    /// by 1.
+    /// Panics if index >= len.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2, 300]);
+    /// assert!(bitvec.remove(2));
+    /// assert!(!bitvec.remove(0));
+    /// assert_eq!(bitvec.len(), 299);
+    /// assert_eq!(bitvec.to_usizes(), vec![0, 298]);
+    /// ```
+    pub fn remove(&mut self, index: usize) -> bool {
+        let flag = self.get_unchecked(index);
+        let (first, end, bit) = self.shift_bounds(index);
+        let top = B::ELEMENT_BIT_WIDTH as u32 - 1;
+        let low = B::ONE_ELEMENT.wrapping_shl(bit) - B::ONE_ELEMENT;
+        // shift `index + 1..` down by 1 in place, walking down so that the lowest bit of every
+        // element carries into the top of the previous one. The last bit becomes 0.
+        let elements = self.elements_mut();
+        let mut carry = B::ZERO_ELEMENT;
+        for e in elements[first + 1..end].iter_mut().rev() {
+            let x = *e;
+            *e = x.wrapping_shr(1) | carry;
+            carry = x.wrapping_shl(top);
+        }
+        let x = elements[first];
+        elements[first] = (x & low) | (x.wrapping_shr(1) & !low) | carry;
+        self.resize(self.nbits - 1, false);
+        flag
+    }
+
+    /// Split this bitvec in two at `at`: this bitvec keeps bits `0..at` and the returned one
+    /// has bits `at..len`, starting from bit 0.
+    /// Panics if at > len.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 2, 300]);
+    /// let bitvec2 = bitvec.split_off(2);
+    /// assert_eq!(bitvec.len(), 2);
+    /// assert_eq!(bitvec.to_usizes(), vec![1]);
+    /// assert_eq!(bitvec2.len(), 299);
+    /// assert_eq!(bitvec2.to_usizes(), vec![0, 298]);
+    /// ```
+    pub fn split_off(&mut self, at: usize) -> Self {
+        if at > self.nbits {
+            panic!("index out of bounds {} > {}", at, self.nbits);
+        }
+        let tail = if at % B::BIT_WIDTH == 0 {
+            // aligned, the blocks can be moved as they are
+            Self {
+                storage: self.storage.split_off(at / B::BIT_WIDTH),
+                nbits: self.nbits - at,
+            }
+        } else {
+            self.extract(at, self.nbits - at)
+        };
+        self.resize(at, false);
+        tail
+    }
+
+    /// Shorten this bitvec to `nbits`, doing nothing if it isn't longer.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::ones(300);
+    /// bitvec.truncate(500);
+    /// assert_eq!(bitvec.len(), 300);
+    /// bitvec.truncate(3);
+    /// assert_eq!(bitvec.len(), 3);
+    /// assert_eq!(bitvec.count_ones(), 3);
+    /// ```
+    pub fn truncate(&mut self, nbits: usize) {
+        if nbits < self.nbits {
+            self.resize(nbits, false);
+        }
+    }
+
+    /// Concatenate `bitvecs` into a new bitvec, their lengths don't need to be aligned.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::concat(&[
+    ///     BitVec::from_slice(&[0, 2]),
+    ///     BitVec::zeros(0),
+    ///     BitVec::ones(300),
+    /// ]);
+    /// assert_eq!(bitvec.len(), 303);
+    /// assert_eq!(bitvec.count_ones(), 302);
+    /// assert_eq!(bitvec.get(1), Some(false));
+    /// ```
+    pub fn concat(bitvecs: &[Self]) -> Self {
+        let nbits = bitvecs.iter().map(|x| x.nbits).sum::<usize>();
+        let mut bitvec = Self {
+            storage: Vec::with_capacity((nbits + B::BIT_WIDTH - 1) / B::BIT_WIDTH),
+            nbits: 0,
+        };
+        for x in bitvecs {
+            bitvec.append_bits(x);
+        }
+        bitvec
+    }
+
+    /// Count the number of elements existing in this bitvec.
+    ///
+    /// Blocks are added 16 at a time with the Harley-Seal carry-save adder, so only one block in
+    /// 16 needs a lane-wise popcount.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec: BitVec = (0..10_000).map(|x| x%2==0).collect();
+    /// assert_eq!(bitvec.count_ones(), 5000);
+    ///
+    /// let bitvec: BitVec = (0..30_000).map(|x| x%3==0).collect();
+    /// assert_eq!(bitvec.count_ones(), 10_000);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        count_ones_slice(&self.storage)
+    }
+
+    /// Count the number of elements existing in this bitvec, before the specified index.
+    /// Panics if index is invalid.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec: BitVec = (0..10_000).map(|x| x%2==0).collect();
+    /// assert_eq!(bitvec.count_ones_before(5000), 2500);
+    ///
+    /// let bitvec: BitVec = (0..30_000).map(|x| x%3==0).collect();
+    /// assert_eq!(bitvec.count_ones_before(10000), 3334);
+    ///
+    /// let bitvec: BitVec = (0..1).map(|x| true).collect();
+    /// assert_eq!(bitvec.count_ones_before(0), 0);
+    /// assert_eq!(bitvec.count_ones_before(1), 1);
+    ///
+    /// let bitvec: BitVec = (0..10).map(|x| true).collect();
+    /// for i in 0..10 {
+    ///     assert_eq!(bitvec.count_ones_before(i), i);
+    /// }
+    ///
+    /// let bitvec: BitVec = (0..10).map(|x| x==0).collect();
+    /// assert_eq!(bitvec.count_ones_before(0), 0);
+    /// for i in 1..10 {
+    ///     assert_eq!(bitvec.count_ones_before(i), 1);
+    /// }
+    ///
+    /// let bitvec: BitVec = (0..31).map(|x| true).collect();
+    /// assert_eq!(bitvec.count_ones_before(0), 0);
+    /// for i in 1..31 {
+    ///     assert_eq!(bitvec.count_ones_before(i), i);
+    /// }
+    ///
+    /// let bitvec: BitVec = (0..32).map(|x| true).collect();
+    /// assert_eq!(bitvec.count_ones_before(0), 0);
+    /// for i in 1..32 {
     ///     assert_eq!(bitvec.count_ones_before(i), i);
     /// }
     /// ```
@@ -823,32 +1871,203 @@ where
             return 0;
         }
         let (i, bytes, bits) = Self::bit_to_len(index);
-        let mut ones = self
-            .storage
-            .iter()
-            .take(i)
-            .map(|x| {
-                x.to_array()
-                    .into_iter()
-                    .map(|a| a.count_ones())
-                    .sum::<u32>()
-            })
-            .sum::<u32>();
+        let mut ones = count_ones_slice(&self.storage[..i]);
         if bytes > 0 || bits > 0 {
             // Safe unwrap here
             let arr = self.storage.get(i).unwrap().to_array();
             ones += arr
                 .into_iter()
                 .take(bytes)
-                .map(|x| x.count_ones())
-                .sum::<u32>();
+                .map(|x| x.count_ones() as usize)
+                .sum::<usize>();
             if bits > 0 {
                 let x = arr.into_iter().nth(bytes).unwrap();
-                ones +=
-                    (x & (B::ONE_ELEMENT.wrapping_shl(bits as u32) - B::ONE_ELEMENT)).count_ones();
+                ones += (x & (B::ONE_ELEMENT.wrapping_shl(bits as u32) - B::ONE_ELEMENT))
+                    .count_ones() as usize;
             }
         }
-        ones as usize
+        ones
+    }
+
+    // count ones of `f(a, b)` for every pair of blocks, missing blocks are treated as 0
+    #[inline]
+    fn zip_count_ones<F: Fn(B, B) -> B>(&self, other: &Self, f: F) -> usize {
+        let len = cmp::max(self.storage.len(), other.storage.len());
+        count_ones_blocks((0..len).map(|i| {
+            let a = self.storage.get(i).cloned().unwrap_or(B::ZERO);
+            let b = other.storage.get(i).cloned().unwrap_or(B::ZERO);
+            f(a, b)
+        }))
+    }
+
+    /// Return true if every element of `self` is also in `other`.
+    ///
+    /// Bitvecs of different lengths are compared as sets, bits beyond the length of a bitvec are
+    /// treated as 0. No allocation is made and the scan stops at the first offending block.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 5]);
+    /// let bitvec2 = BitVec::from_slice(&[1, 2, 3, 4, 5, 1000]);
+    /// assert!(bitvec.is_subset(&bitvec2));
+    /// assert!(!bitvec2.is_subset(&bitvec));
+    /// assert!(bitvec2.is_superset(&bitvec));
+    /// ```
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.storage
+            .iter()
+            .enumerate()
+            .all(|(i, a)| match other.storage.get(i) {
+                Some(b) => a.and_not(*b) == B::ZERO,
+                None => *a == B::ZERO,
+            })
+    }
+
+    /// Return true if every element of `other` is also in `self`.
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Return true if `self` and `other` have no element in common.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 5]);
+    /// assert!(bitvec.is_disjoint(&BitVec::from_slice(&[0, 2, 4, 1000])));
+    /// assert!(!bitvec.is_disjoint(&BitVec::from_slice(&[5])));
+    /// ```
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.storage
+            .iter()
+            .zip(other.storage.iter())
+            .all(|(a, b)| (*a & *b) == B::ZERO)
+    }
+
+    /// Count the elements of `self & other` without allocating.
+    ///
+    /// Bits beyond the length of a bitvec are treated as 0, so the lengths may differ.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 5]);
+    /// let bitvec2 = BitVec::from_slice(&[3, 4, 5, 1000]);
+    /// assert_eq!(bitvec.and_count(&bitvec2), 2);
+    /// assert_eq!(bitvec.or_count(&bitvec2), 5);
+    /// assert_eq!(bitvec.xor_count(&bitvec2), 3);
+    /// assert_eq!(bitvec.andnot_count(&bitvec2), 1);
+    /// ```
+    pub fn and_count(&self, other: &Self) -> usize {
+        self.storage
+            .iter()
+            .zip(other.storage.iter())
+            .map(|(a, b)| B::count_ones(*a & *b))
+            .sum()
+    }
+
+    /// Count the elements of `self | other` without allocating.
+    pub fn or_count(&self, other: &Self) -> usize {
+        self.zip_count_ones(other, |a, b| a | b)
+    }
+
+    /// Count the elements of `self ^ other` without allocating.
+    pub fn xor_count(&self, other: &Self) -> usize {
+        self.zip_count_ones(other, |a, b| a ^ b)
+    }
+
+    /// Count the elements of `self.difference(other)` without allocating.
+    pub fn andnot_count(&self, other: &Self) -> usize {
+        self.zip_count_ones(other, |a, b| a.and_not(b))
+    }
+
+    // (|self|, |other|, |self & other|) in a single pass, missing blocks are treated as 0
+    fn similarity_counts(&self, other: &Self) -> (usize, usize, usize) {
+        let len = cmp::max(self.storage.len(), other.storage.len());
+        (0..len).fold((0, 0, 0), |(x, y, and), i| {
+            let a = self.storage.get(i).cloned().unwrap_or(B::ZERO);
+            let b = other.storage.get(i).cloned().unwrap_or(B::ZERO);
+            (
+                x + B::count_ones(a),
+                y + B::count_ones(b),
+                and + B::count_ones(a & b),
+            )
+        })
+    }
+
+    /// Number of bits that differ between `self` and `other`, i.e. `(self ^ other).count_ones()`.
+    ///
+    /// Bits beyond the length of a bitvec are treated as 0, so the lengths may differ.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 2, 3, 4]);
+    /// let bitvec2 = BitVec::from_slice(&[3, 4, 5, 6, 7, 8]);
+    /// assert_eq!(bitvec.hamming_distance(&bitvec2), 6);
+    /// assert_eq!(bitvec.jaccard(&bitvec2), 0.25);
+    /// assert_eq!(bitvec.tanimoto(&bitvec2), 0.25);
+    /// assert_eq!(bitvec.dice(&bitvec2), 0.4);
+    /// ```
+    pub fn hamming_distance(&self, other: &Self) -> usize {
+        self.xor_count(other)
+    }
+
+    /// Jaccard similarity `|self & other| / |self | other|`.
+    ///
+    /// All popcounts are computed in a single pass without allocation. Two empty sets have a
+    /// similarity of 1.
+    pub fn jaccard(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        jaccard_from_counts(x, y, and)
+    }
+
+    /// Tanimoto similarity, which equals the Jaccard similarity for bit vectors.
+    #[inline]
+    pub fn tanimoto(&self, other: &Self) -> f64 {
+        self.jaccard(other)
+    }
+
+    /// Sørensen–Dice similarity `2 * |self & other| / (|self| + |other|)`.
+    ///
+    /// All popcounts are computed in a single pass without allocation. Two empty sets have a
+    /// similarity of 1.
+    pub fn dice(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        dice_from_counts(x, y, and)
+    }
+
+    /// Cosine similarity `|self & other| / sqrt(|self| * |other|)`.
+    ///
+    /// All popcounts are computed in a single pass without allocation. Two empty sets have a
+    /// similarity of 1, an empty set and a non-empty set have a similarity of 0.
+    ///
+    /// Only available with the `std` feature, since `sqrt` is not provided by `core`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 2, 3, 4]);
+    /// let bitvec2 = BitVec::from_slice(&[3, 4, 5, 6, 7, 8]);
+    /// assert!((bitvec.cosine(&bitvec2) - 2.0 / 24f64.sqrt()).abs() < 1e-12);
+    /// assert_eq!(bitvec.cosine(&BitVec::zeros(10)), 0.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn cosine(&self, other: &Self) -> f64 {
+        let (x, y, and) = self.similarity_counts(other);
+        cosine_from_counts(x, y, and)
     }
 
     /// Count the number of leading zeros in this bitvec.
@@ -886,32 +2105,181 @@ where
                         zero_item_count += 1;
                         true
                     }
-                    false => false,
-                });
-
-            // Safe unwrap here, since there should be at least one non-zero item in arr.
-            let y = x_iter.next().unwrap();
-            let raw_leading_zeros =
-                zero_item_count * B::ELEMENT_BIT_WIDTH + y.leading_zeros() as usize;
-            let mut extra_leading_zeros = self.nbits % B::BIT_WIDTH;
-            if extra_leading_zeros > 0 {
-                extra_leading_zeros = B::BIT_WIDTH - extra_leading_zeros
+                    false => false,
+                });
+
+            // Safe unwrap here, since there should be at least one non-zero item in arr.
+            let y = x_iter.next().unwrap();
+            let raw_leading_zeros =
+                zero_item_count * B::ELEMENT_BIT_WIDTH + y.leading_zeros() as usize;
+            let mut extra_leading_zeros = self.nbits % B::BIT_WIDTH;
+            if extra_leading_zeros > 0 {
+                extra_leading_zeros = B::BIT_WIDTH - extra_leading_zeros
+            }
+            return raw_leading_zeros - extra_leading_zeros;
+        }
+
+        self.nbits
+    }
+
+    // first index >= from whose bit is `!invert`, blocks which contain no such bit are skipped
+    // with a single comparison
+    fn next_match(&self, from: usize, invert: bool) -> Option<usize> {
+        if from >= self.nbits {
+            return None;
+        }
+        let skip = if invert { B::MAX } else { B::ZERO };
+        let load = |k: usize| {
+            let x = self.element(k);
+            if invert {
+                !x
+            } else {
+                x
+            }
+        };
+        let mut k = from / B::ELEMENT_BIT_WIDTH;
+        let mut x = load(k) & B::MAX_ELEMENT.clear_low_bits((from % B::ELEMENT_BIT_WIDTH) as u32);
+        loop {
+            if x != B::ZERO_ELEMENT {
+                let index = k * B::ELEMENT_BIT_WIDTH + x.trailing_zeros() as usize;
+                // an inverted tail is full of ones
+                return if index < self.nbits {
+                    Some(index)
+                } else {
+                    None
+                };
+            }
+            k += 1;
+            if k % B::LANES == 0 {
+                while k / B::LANES < self.storage.len() && self.storage[k / B::LANES] == skip {
+                    k += B::LANES;
+                }
+            }
+            if k / B::LANES >= self.storage.len() {
+                return None;
+            }
+            x = load(k);
+        }
+    }
+
+    // last index <= from whose bit is `!invert`, blocks which contain no such bit are skipped
+    // with a single comparison
+    fn prev_match(&self, from: usize, invert: bool) -> Option<usize> {
+        if self.nbits == 0 {
+            return None;
+        }
+        let from = cmp::min(from, self.nbits - 1);
+        let skip = if invert { B::MAX } else { B::ZERO };
+        let load = |k: usize| {
+            let x = self.element(k);
+            if invert {
+                !x
+            } else {
+                x
+            }
+        };
+        let mut k = from / B::ELEMENT_BIT_WIDTH;
+        let mut x = load(k)
+            .clear_high_bits((B::ELEMENT_BIT_WIDTH - 1 - from % B::ELEMENT_BIT_WIDTH) as u32);
+        loop {
+            if x != B::ZERO_ELEMENT {
+                return Some(
+                    k * B::ELEMENT_BIT_WIDTH + B::ELEMENT_BIT_WIDTH
+                        - 1
+                        - x.leading_zeros() as usize,
+                );
+            }
+            if k == 0 {
+                return None;
+            }
+            k -= 1;
+            if (k + 1) % B::LANES == 0 {
+                while self.storage[k / B::LANES] == skip {
+                    if k < B::LANES {
+                        return None;
+                    }
+                    k -= B::LANES;
+                }
             }
-            return raw_leading_zeros - extra_leading_zeros;
+            x = load(k);
         }
+    }
 
-        self.nbits
+    /// Index of the first set bit, or `None` if the set is empty.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::zeros(1000);
+    /// assert_eq!(bitvec.first_one(), None);
+    /// bitvec.set(300, true);
+    /// bitvec.set(700, true);
+    /// assert_eq!(bitvec.first_one(), Some(300));
+    /// assert_eq!(bitvec.last_one(), Some(700));
+    /// assert_eq!(bitvec.next_one(301), Some(700));
+    /// assert_eq!(bitvec.prev_one(699), Some(300));
+    /// assert_eq!(bitvec.first_zero(), Some(0));
+    /// assert_eq!(bitvec.last_zero(), Some(999));
+    /// assert_eq!(bitvec.trailing_zeros(), 300);
+    /// ```
+    #[inline]
+    pub fn first_one(&self) -> Option<usize> {
+        self.next_match(0, false)
+    }
+
+    /// Index of the last set bit, or `None` if the set is empty.
+    #[inline]
+    pub fn last_one(&self) -> Option<usize> {
+        self.prev_match(usize::MAX, false)
+    }
+
+    /// Index of the first unset bit, or `None` if every bit is set.
+    #[inline]
+    pub fn first_zero(&self) -> Option<usize> {
+        self.next_match(0, true)
+    }
+
+    /// Index of the last unset bit, or `None` if every bit is set.
+    #[inline]
+    pub fn last_zero(&self) -> Option<usize> {
+        self.prev_match(usize::MAX, true)
+    }
+
+    /// Index of the first set bit at or after `from`.
+    #[inline]
+    pub fn next_one(&self, from: usize) -> Option<usize> {
+        self.next_match(from, false)
+    }
+
+    /// Index of the last set bit at or before `from`.
+    #[inline]
+    pub fn prev_one(&self, from: usize) -> Option<usize> {
+        self.prev_match(from, false)
+    }
+
+    /// Index of the first unset bit at or after `from`.
+    #[inline]
+    pub fn next_zero(&self, from: usize) -> Option<usize> {
+        self.next_match(from, true)
+    }
+
+    /// Index of the last unset bit at or before `from`.
+    #[inline]
+    pub fn prev_zero(&self, from: usize) -> Option<usize> {
+        self.prev_match(from, true)
+    }
+
+    /// Count the number of trailing zeros (starting from index 0) in this bitvec.
+    #[inline]
+    pub fn trailing_zeros(&self) -> usize {
+        self.first_one().unwrap_or(self.nbits)
     }
 
     /// return true if contains at least 1 element
     pub fn any(&self) -> bool {
-        self.storage.iter().any(|x| {
-            x.to_array()
-                .into_iter()
-                .map(|a| a.count_ones())
-                .sum::<u32>()
-                > 0
-        })
+        self.storage.iter().any(|x| *x != B::ZERO)
     }
 
     /// return true if contains self.len elements
@@ -946,6 +2314,11 @@ where
     }
 
     /// Return an interator over the indices of bits in this set.
+    ///
+    /// Zero blocks are skipped as a whole, so iterating a sparse bitvec only costs the number of
+    /// non-zero blocks plus the number of set bits. The iterator is double-ended and knows its
+    /// exact length.
+    ///
     /// Example:
     ///
     /// ```rust
@@ -958,19 +2331,15 @@ where
     /// assert_eq!(usize_iter.next(), Some(6));
     /// assert_eq!(usize_iter.next(), Some(9));
     /// assert_eq!(usize_iter.next(), None);
+    ///
+    /// let mut usize_iter = bitvec.usizes();
+    /// assert_eq!(usize_iter.len(), 4);
+    /// assert_eq!(usize_iter.next_back(), Some(9));
+    /// assert_eq!(usize_iter.next(), Some(0));
+    /// assert_eq!(usize_iter.len(), 2);
     /// ```
-    pub fn usizes(&self) -> impl Iterator<Item = usize> + '_ {
-        self.storage
-            .iter()
-            .flat_map(|x| x.to_array())
-            .flat_map(|x| {
-                (0..B::ELEMENT_BIT_WIDTH)
-                    .map(move |i| (x.wrapping_shr(i as u32)) & B::ONE_ELEMENT != B::ZERO_ELEMENT)
-            })
-            .take(self.nbits)
-            .enumerate()
-            .filter(|(_, b)| *b)
-            .map(|(i, _)| i)
+    pub fn usizes(&self) -> Ones<'_, B, L> {
+        Ones::new(&self.storage, self.count_ones())
     }
 
     /// Generate a `Vec<usize>` which only contains the indices of bits in this set.
@@ -989,12 +2358,117 @@ where
     }
 }
 
-impl<B, I: Iterator<Item = bool>, const L: usize> From<I> for BitVecSimd<B, L>
+// convert `range` to `(start, end)`, an unbounded end is `len`
+// panics if start > end
+fn range_to_bounds_unchecked<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let start = match range.start_bound() {
+        Bound::Included(&x) => x,
+        Bound::Excluded(&x) => x.checked_add(1).expect("range bound overflow"),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&x) => x.checked_add(1).expect("range bound overflow"),
+        Bound::Excluded(&x) => x,
+        Bound::Unbounded => len,
+    };
+    assert!(
+        start <= end,
+        "range start {} is greater than end {}",
+        start,
+        end
+    );
+    (start, end)
+}
+
+// similarity of two sets from `(|a|, |b|, |a & b|)`, shared by `BitVecSimd` and `DynBitVec`
+#[inline]
+pub(crate) fn jaccard_from_counts(x: usize, y: usize, and: usize) -> f64 {
+    let or = x + y - and;
+    if or == 0 {
+        1.0
+    } else {
+        and as f64 / or as f64
+    }
+}
+
+#[inline]
+pub(crate) fn dice_from_counts(x: usize, y: usize, and: usize) -> f64 {
+    if x + y == 0 {
+        1.0
+    } else {
+        (2 * and) as f64 / (x + y) as f64
+    }
+}
+
+#[cfg(feature = "std")]
+#[inline]
+pub(crate) fn cosine_from_counts(x: usize, y: usize, and: usize) -> f64 {
+    match (x, y) {
+        (0, 0) => 1.0,
+        (0, _) | (_, 0) => 0.0,
+        _ => and as f64 / (x as f64 * y as f64).sqrt(),
+    }
+}
+
+// convert `range` to `(start, end)`, panics if it is not inside of `0..len`
+fn range_to_bounds<R: RangeBounds<usize>>(range: R, len: usize) -> (usize, usize) {
+    let (start, end) = range_to_bounds_unchecked(range, len);
+    assert!(
+        end <= len,
+        "range {}..{} out of bounds for length {}",
+        start,
+        end,
+        len
+    );
+    (start, end)
+}
+
+impl<B, const L: usize> FromIterator<bool> for BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+        Self::from_bool_iterator(iter.into_iter())
+    }
+}
+
+impl<B, const L: usize> Extend<bool> for BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn extend<I: IntoIterator<Item = bool>>(&mut self, iter: I) {
+        self.extend_bools(iter.into_iter());
+    }
+}
+
+impl<'a, B, const L: usize> Extend<&'a bool> for BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn extend<I: IntoIterator<Item = &'a bool>>(&mut self, iter: I) {
+        self.extend_bools(iter.into_iter().copied());
+    }
+}
+
+impl<B, const L: usize> FromIterator<usize> for BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn from_iter<I: IntoIterator<Item = usize>>(iter: I) -> Self {
+        let mut bitvec = Self::zeros(0);
+        bitvec.extend(iter);
+        bitvec
+    }
+}
+
+impl<B, const L: usize> Extend<usize> for BitVecSimd<B, L>
 where
     B: BitBlock<L>,
 {
-    fn from(i: I) -> Self {
-        Self::from_bool_iterator(i)
+    /// Set the bits at the indices of `iter`, growing the bitvec to one past the largest index
+    /// if needed.
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, iter: I) {
+        self.set_indices(iter.into_iter(), true);
     }
 }
 
@@ -1070,13 +2544,15 @@ impl_trait! {
 
 macro_rules! impl_eq_fn {
     ($( $rhs:tt )+) => {
-        // eq should always ignore the bits > nbits
+        // bits > nbits are always 0, so comparing storage is enough
+        // bitvecs with different lengths are never equal
         fn eq(&self, other: $( $rhs )+) -> bool {
-            assert_eq!(self.nbits, other.nbits);
-            self.storage
-                .iter()
-                .zip(other.storage.iter())
-                .all(|(a, b)| a == b)
+            self.nbits == other.nbits
+                && self
+                    .storage
+                    .iter()
+                    .zip(other.storage.iter())
+                    .all(|(a, b)| a == b)
         }
     }
 }
@@ -1086,6 +2562,74 @@ impl_trait! { (PartialEq< &BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_eq_fn
 impl_trait! { (PartialEq< &mut BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_eq_fn!(&&mut Self); } }
 impl_trait! { (PartialEq< BitVecSimd<B, L> >), (&BitVecSimd<B, L>), { impl_eq_fn!(&BitVecSimd<B, L>); } }
 impl_trait! { (PartialEq< BitVecSimd<B, L> >), (&mut BitVecSimd<B, L>), { impl_eq_fn!(&BitVecSimd<B, L>); } }
+impl_trait! { (Eq), (BitVecSimd<B, L>), {} }
+
+impl_trait! {
+    (Hash),
+    (BitVecSimd<B, L>),
+    {
+        // consistent with `eq` since bits > nbits are always 0
+        fn hash<H: Hasher>(&self, state: &mut H) {
+            self.nbits.hash(state);
+            for x in self.storage.iter() {
+                for a in x.to_array().iter() {
+                    a.hash(state);
+                }
+            }
+        }
+    }
+}
+
+impl_trait! {
+    (PartialOrd),
+    (BitVecSimd<B, L>),
+    {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+}
+
+impl_trait! {
+    (Ord),
+    (BitVecSimd<B, L>),
+    {
+        /// Bitvecs are ordered lexicographically by their bits starting from index 0, exactly
+        /// like the `Vec<bool>` they convert to: at the first index where they differ, the bitvec
+        /// containing `false` is less. If one bitvec is a prefix of the other, the shorter one
+        /// is less.
+        fn cmp(&self, other: &Self) -> Ordering {
+            let nbits = cmp::min(self.nbits, other.nbits);
+            let elements = (nbits + B::ELEMENT_BIT_WIDTH - 1) / B::ELEMENT_BIT_WIDTH;
+            for (i, (a, b)) in self.storage.iter().zip(other.storage.iter()).enumerate() {
+                if a == b {
+                    continue;
+                }
+                let (a, b) = (a.to_array(), b.to_array());
+                for j in 0..B::LANES {
+                    let k = i * B::LANES + j;
+                    if k >= elements {
+                        break;
+                    }
+                    let diff = a[j] ^ b[j];
+                    if diff != B::ZERO_ELEMENT {
+                        let index = k * B::ELEMENT_BIT_WIDTH + diff.trailing_zeros() as usize;
+                        if index >= nbits {
+                            break;
+                        }
+                        return if self.get_unchecked(index) {
+                            Ordering::Greater
+                        } else {
+                            Ordering::Less
+                        };
+                    }
+                }
+                break;
+            }
+            self.nbits.cmp(&other.nbits)
+        }
+    }
+}
 
 macro_rules! impl_bit_op_fn {
     ($fn:ident, $op:ident, ( $( $rhs:tt )+ )) =>
@@ -1121,6 +2665,7 @@ macro_rules! impl_bit_op {
 impl_bit_op!(BitAnd, bitand, and, and_cloned);
 impl_bit_op!(BitOr, bitor, or, or_cloned);
 impl_bit_op!(BitXor, bitxor, xor, xor_cloned);
+impl_bit_op!(Sub, sub, difference, difference_cloned);
 
 macro_rules! impl_not_fn {
     () => {
@@ -1135,6 +2680,31 @@ impl_trait! {(Not), (BitVecSimd<B, L>), { impl_not_fn!(); }}
 impl_trait! {(Not), (&BitVecSimd<B, L>), { impl_not_fn!(); }}
 impl_trait! {(Not), (&mut BitVecSimd<B, L>), { impl_not_fn!(); }}
 
+macro_rules! impl_shift_fn {
+    ($fn:ident, $op_inplace:ident, $op_cloned:ident, mut) => {
+        type Output = BitVecSimd<B, L>;
+        fn $fn(mut self, rhs: usize) -> Self::Output {
+            self.$op_inplace(rhs);
+            self
+        }
+    };
+    ($fn:ident, $op_inplace:ident, $op_cloned:ident) => {
+        type Output = BitVecSimd<B, L>;
+        fn $fn(self, rhs: usize) -> Self::Output {
+            self.$op_cloned(rhs)
+        }
+    };
+}
+
+impl_trait! {(Shl<usize>), (BitVecSimd<B, L>), { impl_shift_fn!(shl, shl_inplace, shl_cloned, mut); }}
+impl_trait! {(Shl<usize>), (&BitVecSimd<B, L>), { impl_shift_fn!(shl, shl_inplace, shl_cloned); }}
+impl_trait! {(Shl<usize>), (&mut BitVecSimd<B, L>), { impl_shift_fn!(shl, shl_inplace, shl_cloned); }}
+impl_trait! {(Shr<usize>), (BitVecSimd<B, L>), { impl_shift_fn!(shr, shr_inplace, shr_cloned, mut); }}
+impl_trait! {(Shr<usize>), (&BitVecSimd<B, L>), { impl_shift_fn!(shr, shr_inplace, shr_cloned); }}
+impl_trait! {(Shr<usize>), (&mut BitVecSimd<B, L>), { impl_shift_fn!(shr, shr_inplace, shr_cloned); }}
+impl_trait! {(ShlAssign<usize>), (BitVecSimd<B, L>), { fn shl_assign(&mut self, rhs: usize) { self.shl_inplace(rhs); } }}
+impl_trait! {(ShrAssign<usize>), (BitVecSimd<B, L>), { fn shr_assign(&mut self, rhs: usize) { self.shr_inplace(rhs); } }}
+
 macro_rules! impl_bit_assign_fn {
     (($( $rhs:tt )+), $fn:ident, $fn1:ident, &) => {
         fn $fn(&mut self, rhs: $( $rhs )+) {
@@ -1157,6 +2727,9 @@ impl_trait! {(BitOrAssign< &mut BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_
 impl_trait! {(BitXorAssign), (BitVecSimd<B, L>), { impl_bit_assign_fn!((Self), bitxor_assign, xor_inplace, &); } }
 impl_trait! {(BitXorAssign< &BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_bit_assign_fn!((&BitVecSimd<B, L>), bitxor_assign, xor_inplace); } }
 impl_trait! {(BitXorAssign< &mut BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_bit_assign_fn!((&mut BitVecSimd<B, L>), bitxor_assign, xor_inplace); } }
+impl_trait! {(SubAssign), (BitVecSimd<B, L>), { impl_bit_assign_fn!((Self), sub_assign, difference_inplace, &); } }
+impl_trait! {(SubAssign< &BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_bit_assign_fn!((&BitVecSimd<B, L>), sub_assign, difference_inplace); } }
+impl_trait! {(SubAssign< &mut BitVecSimd<B, L> >), (BitVecSimd<B, L>), { impl_bit_assign_fn!((&mut BitVecSimd<B, L>), sub_assign, difference_inplace); } }
 
 // BitBlockElement is the element of a SIMD type BitBlock
 #[cfg(not(feature = "use_serde"))]
@@ -1172,6 +2745,8 @@ pub trait BitBlockElement:
     + Add<Output = Self>
     + Sub<Output = Self>
     + PartialEq
+    + Eq
+    + Hash
     + Sized
     + Copy
     + Clone
@@ -1185,10 +2760,15 @@ pub trait BitBlockElement:
 
     fn count_ones(self) -> u32;
     fn leading_zeros(self) -> u32;
+    fn trailing_zeros(self) -> u32;
     fn wrapping_shl(self, rhs: u32) -> Self;
     fn wrapping_shr(self, rhs: u32) -> Self;
     fn clear_high_bits(self, rhs: u32) -> Self;
     fn clear_low_bits(self, rhs: u32) -> Self;
+    /// Read from little-endian `bytes`, missing high bytes are zero and extra bytes are ignored.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    /// Write as little-endian into `out`, truncated to the length of `out`.
+    fn write_le_slice(self, out: &mut [u8]);
 }
 
 #[cfg(feature = "use_serde")]
@@ -1204,6 +2784,8 @@ pub trait BitBlockElement:
     + Add<Output = Self>
     + Sub<Output = Self>
     + PartialEq
+    + Eq
+    + Hash
     + Sized
     + Copy
     + Clone
@@ -1219,10 +2801,15 @@ pub trait BitBlockElement:
 
     fn count_ones(self) -> u32;
     fn leading_zeros(self) -> u32;
+    fn trailing_zeros(self) -> u32;
     fn wrapping_shl(self, rhs: u32) -> Self;
     fn wrapping_shr(self, rhs: u32) -> Self;
     fn clear_high_bits(self, rhs: u32) -> Self;
     fn clear_low_bits(self, rhs: u32) -> Self;
+    /// Read from little-endian `bytes`, missing high bytes are zero and extra bytes are ignored.
+    fn from_le_slice(bytes: &[u8]) -> Self;
+    /// Write as little-endian into `out`, truncated to the length of `out`.
+    fn write_le_slice(self, out: &mut [u8]);
 }
 
 macro_rules! impl_BitBlockElement {
@@ -1243,6 +2830,11 @@ macro_rules! impl_BitBlockElement {
                 Self::leading_zeros(self)
             }
 
+            #[inline]
+            fn trailing_zeros(self) -> u32 {
+                Self::trailing_zeros(self)
+            }
+
             #[inline]
             fn wrapping_shl(self, rhs: u32) -> Self {
                 self.wrapping_shl(rhs)
@@ -1262,6 +2854,21 @@ macro_rules! impl_BitBlockElement {
             fn clear_low_bits(self, rhs: u32) -> Self {
                 self.wrapping_shr(rhs).wrapping_shl(rhs)
             }
+
+            #[inline]
+            fn from_le_slice(bytes: &[u8]) -> Self {
+                let mut buf = [0u8; core::mem::size_of::<$type>()];
+                let n = cmp::min(bytes.len(), buf.len());
+                buf[..n].copy_from_slice(&bytes[..n]);
+                Self::from_le_bytes(buf)
+            }
+
+            #[inline]
+            fn write_le_slice(self, out: &mut [u8]) {
+                let buf = self.to_le_bytes();
+                let n = cmp::min(out.len(), buf.len());
+                out[..n].copy_from_slice(&buf[..n]);
+            }
         }
     };
 }
@@ -1270,12 +2877,20 @@ impl_BitBlockElement!(u8, 0u8, 1u8, 0xFFu8);
 impl_BitBlockElement!(u16, 0u16, 1u16, 0xFFFFu16);
 impl_BitBlockElement!(u32, 0u32, 1u32, 0xFFFFFFFFu32);
 impl_BitBlockElement!(u64, 0u64, 1u64, 0xFFFFFFFFFFFFFFFFu64);
+impl_BitBlockElement!(u128, 0u128, 1u128, u128::MAX);
 
 // BitBlock is the basic building block for internal storage
 // BitVec is expected to be aligned properly
-pub trait BitBlock<const L: usize>:
-    Not<Output = Self>
-    + BitAnd<Output = Self>
+/// A SIMD vector or integer used as a block of `L` [`BitBlockElement`]s.
+///
+/// # Safety
+///
+/// `Self` must have the same memory layout as `[Self::Element; L]`: the same size, at least its
+/// alignment, and no padding. `Self::Element` must be a plain integer without padding for which
+/// every bit pattern is valid. The storage of a [`BitVecSimd`] is viewed as elements and bytes
+/// through this contract.
+pub unsafe trait BitBlock<const L: usize>:
+    BitAnd<Output = Self>
     + BitOr<Output = Self>
     + BitXor<Output = Self>
     + Add<Output = Self>
@@ -1286,7 +2901,6 @@ pub trait BitBlock<const L: usize>:
     + Clone
     + fmt::Debug
     + From<Self::Element>
-    + From<[Self::Element; L]>
 {
     type Element: BitBlockElement;
     const BIT_WIDTH: usize;
@@ -1298,21 +2912,148 @@ pub trait BitBlock<const L: usize>:
     const ZERO: Self;
     const MAX: Self;
     fn to_array(self) -> [Self::Element; L];
+    fn from_array(arr: [Self::Element; L]) -> Self;
     fn and_inplace(&mut self, rhs: &Self);
     fn or_inplace(&mut self, rhs: &Self);
     fn xor_inplace(&mut self, rhs: &Self);
+
+    /// `self & !rhs`
+    #[inline]
+    fn and_not(self, rhs: Self) -> Self {
+        self & (rhs ^ Self::MAX)
+    }
+
+    /// Number of ones in this block, summed over its lanes.
+    ///
+    /// Counting a whole bitvec only calls this for about one block in 16, see
+    /// [`BitVecSimd::count_ones`].
+    #[inline]
+    fn count_ones(self) -> usize {
+        self.to_array()
+            .iter()
+            .map(|x| BitBlockElement::count_ones(*x) as usize)
+            .sum()
+    }
+}
+
+// carry-save adder over every bit of the blocks: returns the (carry, sum) bits of `a + b + c`
+#[inline]
+fn csa<B: BitBlock<L>, const L: usize>(a: B, b: B, c: B) -> (B, B) {
+    let u = a ^ b;
+    ((a & b) | (u & c), u ^ c)
+}
+
+// Harley-Seal popcount: every 16 blocks are added bitwise into the `ones`, `twos`, `fours` and
+// `eights` counters with carry-save adders, only the carried-out `sixteens` block needs a lane-wise
+// popcount. Blocks that don't fill a group of 16 are counted one by one.
+struct HarleySeal<B> {
+    ones: B,
+    twos: B,
+    fours: B,
+    eights: B,
+    sixteens: usize,
+}
+
+impl<B> HarleySeal<B> {
+    #[inline]
+    fn new<const L: usize>() -> Self
+    where
+        B: BitBlock<L>,
+    {
+        HarleySeal {
+            ones: B::ZERO,
+            twos: B::ZERO,
+            fours: B::ZERO,
+            eights: B::ZERO,
+            sixteens: 0,
+        }
+    }
+
+    #[inline]
+    fn add<const L: usize>(&mut self, group: &[B; 16])
+    where
+        B: BitBlock<L>,
+    {
+        let (twos_a, ones) = csa(self.ones, group[0], group[1]);
+        let (twos_b, ones) = csa(ones, group[2], group[3]);
+        let (fours_a, twos) = csa(self.twos, twos_a, twos_b);
+        let (twos_a, ones) = csa(ones, group[4], group[5]);
+        let (twos_b, ones) = csa(ones, group[6], group[7]);
+        let (fours_b, twos) = csa(twos, twos_a, twos_b);
+        let (eights_a, fours) = csa(self.fours, fours_a, fours_b);
+        let (twos_a, ones) = csa(ones, group[8], group[9]);
+        let (twos_b, ones) = csa(ones, group[10], group[11]);
+        let (fours_a, twos) = csa(twos, twos_a, twos_b);
+        let (twos_a, ones) = csa(ones, group[12], group[13]);
+        let (twos_b, ones) = csa(ones, group[14], group[15]);
+        let (fours_b, twos) = csa(twos, twos_a, twos_b);
+        let (eights_b, fours) = csa(fours, fours_a, fours_b);
+        let (sixteens, eights) = csa(self.eights, eights_a, eights_b);
+        self.sixteens += sixteens.count_ones();
+        self.ones = ones;
+        self.twos = twos;
+        self.fours = fours;
+        self.eights = eights;
+    }
+
+    #[inline]
+    fn finish<const L: usize>(self, rest: &[B]) -> usize
+    where
+        B: BitBlock<L>,
+    {
+        16 * self.sixteens
+            + 8 * self.eights.count_ones()
+            + 4 * self.fours.count_ones()
+            + 2 * self.twos.count_ones()
+            + self.ones.count_ones()
+            + rest.iter().map(|b| b.count_ones()).sum::<usize>()
+    }
+}
+
+// number of ones in `blocks` with the Harley-Seal popcount
+fn count_ones_slice<B: BitBlock<L>, const L: usize>(blocks: &[B]) -> usize {
+    let mut hs = HarleySeal::new();
+    let groups = blocks.chunks_exact(16);
+    let rest = groups.remainder();
+    for group in groups {
+        hs.add(group.try_into().unwrap());
+    }
+    hs.finish(rest)
+}
+
+// number of ones in the blocks produced by `blocks` with the Harley-Seal popcount
+fn count_ones_blocks<B, I, const L: usize>(blocks: I) -> usize
+where
+    B: BitBlock<L>,
+    I: IntoIterator<Item = B>,
+{
+    let mut blocks = blocks.into_iter();
+    let mut hs = HarleySeal::new();
+    loop {
+        let mut group = [B::ZERO; 16];
+        let n = group
+            .iter_mut()
+            .zip(&mut blocks)
+            .map(|(g, b)| *g = b)
+            .count();
+        if n < 16 {
+            return hs.finish(&group[..n]);
+        }
+        hs.add(&group);
+    }
 }
 
 macro_rules! impl_BitBlock {
     ($type: ty, $elem_type: ty, $lanes: expr) => {
-        impl BitBlock<$lanes> for $type {
+        // SAFETY: wide's vectors are plain arrays of `$lanes` `$elem_type` without padding
+        unsafe impl BitBlock<$lanes> for $type {
             type Element = $elem_type;
             const BIT_WIDTH: usize = <$type>::BITS as usize;
-            const ELEMENT_BIT_WIDTH: usize = <$elem_type>::BIT_WIDTH;
+            const ELEMENT_BIT_WIDTH: usize = <$elem_type as BitBlockElement>::BIT_WIDTH;
             const LANES: usize = $lanes;
-            const ZERO_ELEMENT: $elem_type = <$elem_type>::ZERO;
-            const ONE_ELEMENT: $elem_type = <$elem_type>::ONE;
-            const MAX_ELEMENT: $elem_type = <$elem_type>::MAX;
+            const ZERO_ELEMENT: $elem_type = <$elem_type as BitBlockElement>::ZERO;
+            const ONE_ELEMENT: $elem_type = <$elem_type as BitBlockElement>::ONE;
+            const MAX_ELEMENT: $elem_type = <$elem_type as BitBlockElement>::MAX;
             const ZERO: Self = <$type>::ZERO;
             const MAX: Self = <$type>::MAX;
 
@@ -1321,6 +3062,11 @@ macro_rules! impl_BitBlock {
                 <$type>::to_array(self)
             }
 
+            #[inline]
+            fn from_array(arr: [$elem_type; $lanes]) -> Self {
+                <$type>::from(arr)
+            }
+
             #[inline]
             fn and_inplace(&mut self, rhs: &Self) {
                 *self &= rhs;
@@ -1345,21 +3091,73 @@ impl_BitBlock!(u32x4, u32, 4);
 impl_BitBlock!(u32x8, u32, 8);
 impl_BitBlock!(u64x2, u64, 2);
 impl_BitBlock!(u64x4, u64, 4);
+impl_BitBlock!(u8x32, u8, 32);
+impl_BitBlock!(u16x16, u16, 16);
+
+// a scalar integer as a block of one element, for tiny bitvecs or targets without SIMD
+macro_rules! impl_BitBlock_scalar {
+    ($type: ty) => {
+        // SAFETY: an integer has the layout of an array of one integer
+        unsafe impl BitBlock<1> for $type {
+            type Element = $type;
+            const BIT_WIDTH: usize = <$type>::BITS as usize;
+            const ELEMENT_BIT_WIDTH: usize = <$type>::BITS as usize;
+            const LANES: usize = 1;
+            const ZERO_ELEMENT: $type = 0;
+            const ONE_ELEMENT: $type = 1;
+            const MAX_ELEMENT: $type = <$type>::MAX;
+            const ZERO: Self = 0;
+            const MAX: Self = <$type>::MAX;
+
+            #[inline]
+            fn to_array(self) -> [$type; 1] {
+                [self]
+            }
+
+            #[inline]
+            fn from_array(arr: [$type; 1]) -> Self {
+                arr[0]
+            }
+
+            #[inline]
+            fn and_inplace(&mut self, rhs: &Self) {
+                *self &= *rhs;
+            }
+
+            #[inline]
+            fn or_inplace(&mut self, rhs: &Self) {
+                *self |= *rhs;
+            }
+
+            #[inline]
+            fn xor_inplace(&mut self, rhs: &Self) {
+                *self ^= *rhs;
+            }
+
+            #[inline]
+            fn count_ones(self) -> usize {
+                <$type>::count_ones(self) as usize
+            }
+        }
+    };
+}
+
+impl_BitBlock_scalar!(u64);
+impl_BitBlock_scalar!(u128);
 
 #[cfg(feature = "use_serde")]
-fn serialize<S, B, const L: usize>(x: &Vec<B>, s: S) -> Result<S::Ok, S::Error>
+fn serialize<S, B, const L: usize>(x: &[B], s: S) -> Result<S::Ok, S::Error>
 where
     S: Serializer,
     B: BitBlock<L>,
 {
-    let last_count = if let Some(last) = x.last() {
+    // trailing zero elements of the last block are omitted
+    let last_count = x.last().map_or(0, |last| {
         last.to_array()
             .iter()
-            .take_while(|e| **e != <B as BitBlock<L>>::ZERO_ELEMENT)
-            .count()
-    } else {
-        0
-    };
+            .rposition(|e| *e != B::ZERO_ELEMENT)
+            .map_or(0, |i| i + 1)
+    });
 
     let prefix_len = cmp::max(x.len(), 1) - 1;
     let mut seq = s.serialize_seq(Some(prefix_len * L + last_count))?;
@@ -1376,54 +3174,77 @@ where
     seq.end()
 }
 
+/// A bitvec of `nbits` zeros, allocated without trusting `nbits`.
+///
+/// Returns an error instead of aborting if the storage can't be allocated.
 #[cfg(feature = "use_serde")]
-fn deserialize<'de, D, B, T, const L: usize>(deserializer: D) -> Result<Vec<B>, D::Error>
+pub(crate) fn try_zeros<E, B, const L: usize>(nbits: usize) -> Result<BitVecSimd<B, L>, E>
 where
-    D: Deserializer<'de>,
-    B: From<[T; L]>,
-    T: DeserializeOwned + Clone + Default + Copy,
+    E: serde::de::Error,
+    B: BitBlock<L>,
 {
-    struct SeqVisitor<K>(PhantomData<fn() -> K>);
+    let len = nbits / B::BIT_WIDTH + (nbits % B::BIT_WIDTH != 0) as usize;
+    let mut storage = Vec::new();
+    storage
+        .try_reserve_exact(len)
+        .map_err(|_| E::custom(format_args!("nbits {} is too large to allocate", nbits)))?;
+    storage.resize(len, B::ZERO);
+    Ok(BitVecSimd { storage, nbits })
+}
 
-    impl<'de, K> Visitor<'de> for SeqVisitor<K>
-    where
-        K: Deserialize<'de>,
+/// Rebuild a bitvec of `nbits` bits from its storage elements, missing trailing elements are zero.
+///
+/// Returns an error if there are more elements than `nbits` needs, if any bit at or above
+/// `nbits` is set, or if `nbits` is too large to allocate.
+#[cfg(feature = "use_serde")]
+pub(crate) fn from_elements<E, B, const L: usize>(
+    elements: Vec<B::Element>,
+    nbits: usize,
+) -> Result<BitVecSimd<B, L>, E>
+where
+    E: serde::de::Error,
+    B: BitBlock<L>,
+{
+    let len = nbits / B::ELEMENT_BIT_WIDTH + (nbits % B::ELEMENT_BIT_WIDTH != 0) as usize;
+    if elements.len() > len {
+        return Err(E::custom(format_args!(
+            "{} elements are too many for {} bits",
+            elements.len(),
+            nbits
+        )));
+    }
+    let bits = nbits % B::ELEMENT_BIT_WIDTH;
+    if elements.len() == len
+        && bits > 0
+        && elements[len - 1].clear_low_bits(bits as u32) != B::ZERO_ELEMENT
     {
-        type Value = Vec<K>;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a nonempty sequence of numbers")
-        }
-
-        fn visit_seq<S>(self, mut seq: S) -> Result<Vec<K>, S::Error>
-        where
-            S: SeqAccess<'de>,
-        {
-            let mut result = Vec::new();
-            while let Some(value) = seq.next_element()? {
-                result.push(value);
-            }
-            Ok(result)
-        }
+        return Err(E::custom(format_args!("set bits beyond nbits {}", nbits)));
     }
-    let visitor = SeqVisitor(PhantomData);
-    let s: Vec<T> = deserializer.deserialize_seq(visitor)?;
+    let mut bitvec = try_zeros(nbits)?;
+    bitvec.as_mut_elements()[..elements.len()].copy_from_slice(&elements);
+    Ok(bitvec)
+}
 
-    let len = (s.len() + (L - 1)) / L;
-    let mut vec = Vec::<B>::with_capacity(len);
-    for i in 0..len {
-        let k = i * L;
-        let mut arr: [T; L] = [T::default(); L];
-        if k + L < len * L {
-            arr.clone_from_slice(&s[k..k + L]);
-        } else {
-            for j in k..s.len() {
-                arr[j - k] = s[j];
-            }
+#[cfg(feature = "use_serde")]
+impl<'de, B, const L: usize> Deserialize<'de> for BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        // same layout as the derived `Serialize`, validated before building the bitvec
+        #[derive(Deserialize)]
+        #[serde(rename = "BitVecSimd")]
+        struct Raw<E> {
+            storage: Vec<E>,
+            nbits: usize,
         }
-        vec.push(arr.into());
+
+        let raw = Raw::<B::Element>::deserialize(deserializer)?;
+        from_elements(raw.storage, raw.nbits)
     }
-    Ok(vec)
 }
 
 // Declare the default BitVec type
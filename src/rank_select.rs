@@ -0,0 +1,232 @@
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use crate::{BitBlock, BitBlockElement, BitVecSimd};
+
+// number of blocks sharing one absolute count, every relative count must fit in a `u16`
+const BLOCKS_PER_SUPERBLOCK: usize = 32;
+
+/// A `BitVecSimd` with an auxiliary rank/select directory.
+///
+/// The directory stores the number of ones before every superblock (32 blocks) as `usize` and the
+/// number of ones before every block relative to its superblock as `u16`. `rank` is answered in
+/// O(1) and `select` in O(log n).
+///
+/// The bitvec is owned by the directory, so it can only be mutated through
+/// [`RankSelect::update`], which rebuilds the directory afterwards.
+///
+/// Example:
+///
+/// ```rust
+/// use bitvec_simd::{BitVec, RankSelect};
+///
+/// let bitvec: BitVec = (0..1000).map(|x| x % 3 == 0).collect();
+/// let mut rs = RankSelect::new(bitvec);
+/// assert_eq!(rs.rank1(10), 4);
+/// assert_eq!(rs.rank0(10), 6);
+/// assert_eq!(rs.select1(4), Some(12));
+/// assert_eq!(rs.select0(1), Some(2));
+/// assert_eq!(rs.select1(334), None);
+///
+/// rs.update(|bitvec| bitvec.set(1, true));
+/// assert_eq!(rs.rank1(10), 5);
+/// assert_eq!(rs.select1(1), Some(1));
+/// ```
+#[derive(Debug, Clone)]
+pub struct RankSelect<B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    bitvec: BitVecSimd<B, L>,
+    // ones before each superblock
+    superblocks: Vec<usize>,
+    // ones before each block, relative to the superblock containing it
+    blocks: Vec<u16>,
+    ones: usize,
+}
+
+impl<B, const L: usize> RankSelect<B, L>
+where
+    B: BitBlock<L>,
+{
+    /// Build the rank/select directory for `bitvec`.
+    pub fn new(bitvec: BitVecSimd<B, L>) -> Self {
+        let mut rs = Self {
+            bitvec,
+            superblocks: Vec::new(),
+            blocks: Vec::new(),
+            ones: 0,
+        };
+        rs.rebuild();
+        rs
+    }
+
+    fn rebuild(&mut self) {
+        self.superblocks.clear();
+        self.blocks.clear();
+        self.blocks.reserve(self.bitvec.storage.len());
+        let mut ones = 0;
+        let mut relative = 0;
+        for (i, block) in self.bitvec.storage.iter().enumerate() {
+            if i % BLOCKS_PER_SUPERBLOCK == 0 {
+                self.superblocks.push(ones);
+                relative = 0;
+            }
+            self.blocks.push(relative as u16);
+            let count = block.count_ones();
+            ones += count;
+            relative += count;
+        }
+        self.ones = ones;
+    }
+
+    // index of the `rank`-th (from 0) one inside of `x`
+    #[inline]
+    fn select_in_element(mut x: B::Element, rank: usize) -> usize {
+        for _ in 0..rank {
+            x &= x - B::ONE_ELEMENT;
+        }
+        x.trailing_zeros() as usize
+    }
+
+    // largest `i` in `0..len` with `pred(i)`, `pred` must be true for 0 and monotone
+    fn last_true<F: Fn(usize) -> bool>(len: usize, pred: F) -> usize {
+        let (mut lo, mut hi) = (0, len);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            if pred(mid) {
+                lo = mid;
+            } else {
+                hi = mid;
+            }
+        }
+        lo
+    }
+
+    /// The underlying bitvec.
+    #[inline]
+    pub fn bitvec(&self) -> &BitVecSimd<B, L> {
+        &self.bitvec
+    }
+
+    /// Drop the directory and return the underlying bitvec.
+    #[inline]
+    pub fn into_inner(self) -> BitVecSimd<B, L> {
+        self.bitvec
+    }
+
+    /// Mutate the underlying bitvec with `f` and rebuild the directory.
+    pub fn update<R, F: FnOnce(&mut BitVecSimd<B, L>) -> R>(&mut self, f: F) -> R {
+        let result = f(&mut self.bitvec);
+        self.rebuild();
+        result
+    }
+
+    /// Length of the underlying bitvec.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bitvec.len()
+    }
+
+    /// Return true if the underlying bitvec has no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bitvec.len() == 0
+    }
+
+    /// Number of ones in the underlying bitvec.
+    #[inline]
+    pub fn count_ones(&self) -> usize {
+        self.ones
+    }
+
+    /// Number of ones in `0..index`, same as `count_ones_before`.
+    /// Panics if index > len.
+    pub fn rank1(&self, index: usize) -> usize {
+        assert!(index <= self.bitvec.nbits);
+        let (i, bytes, bits) = BitVecSimd::<B, L>::bit_to_len(index);
+        if i == self.bitvec.storage.len() {
+            return self.ones;
+        }
+        let mut ones = self.superblocks[i / BLOCKS_PER_SUPERBLOCK] + self.blocks[i] as usize;
+        let arr = self.bitvec.storage[i].to_array();
+        ones += arr.iter().take(bytes).map(|x| x.count_ones()).sum::<u32>() as usize;
+        if bits > 0 {
+            ones += arr[bytes]
+                .clear_high_bits((B::ELEMENT_BIT_WIDTH - bits) as u32)
+                .count_ones() as usize;
+        }
+        ones
+    }
+
+    /// Number of zeros in `0..index`.
+    /// Panics if index > len.
+    #[inline]
+    pub fn rank0(&self, index: usize) -> usize {
+        index - self.rank1(index)
+    }
+
+    /// Index of the `rank`-th one (counting from 0), or `None` if there are not enough ones.
+    pub fn select1(&self, rank: usize) -> Option<usize> {
+        if rank >= self.ones {
+            return None;
+        }
+        let s = Self::last_true(self.superblocks.len(), |s| self.superblocks[s] <= rank);
+        let start = s * BLOCKS_PER_SUPERBLOCK;
+        let rank = rank - self.superblocks[s];
+        let end = (start + BLOCKS_PER_SUPERBLOCK).min(self.blocks.len());
+        let b = start + Self::last_true(end - start, |b| self.blocks[start + b] as usize <= rank);
+        let mut rank = rank - self.blocks[b] as usize;
+        for (j, x) in self.bitvec.storage[b].to_array().into_iter().enumerate() {
+            let ones = x.count_ones() as usize;
+            if rank < ones {
+                return Some(
+                    b * B::BIT_WIDTH + j * B::ELEMENT_BIT_WIDTH + Self::select_in_element(x, rank),
+                );
+            }
+            rank -= ones;
+        }
+        unreachable!("rank/select directory is out of sync with the bitvec")
+    }
+
+    /// Index of the `rank`-th zero (counting from 0), or `None` if there are not enough zeros.
+    pub fn select0(&self, rank: usize) -> Option<usize> {
+        if rank >= self.bitvec.nbits - self.ones {
+            return None;
+        }
+        let superblock_bits = BLOCKS_PER_SUPERBLOCK * B::BIT_WIDTH;
+        let s = Self::last_true(self.superblocks.len(), |s| {
+            s * superblock_bits - self.superblocks[s] <= rank
+        });
+        let start = s * BLOCKS_PER_SUPERBLOCK;
+        let rank = rank - (s * superblock_bits - self.superblocks[s]);
+        let end = (start + BLOCKS_PER_SUPERBLOCK).min(self.blocks.len());
+        let b = start
+            + Self::last_true(end - start, |b| {
+                b * B::BIT_WIDTH - self.blocks[start + b] as usize <= rank
+            });
+        let mut rank = rank - ((b - start) * B::BIT_WIDTH - self.blocks[b] as usize);
+        for (j, x) in self.bitvec.storage[b].to_array().into_iter().enumerate() {
+            let zeros = B::ELEMENT_BIT_WIDTH - x.count_ones() as usize;
+            if rank < zeros {
+                return Some(
+                    b * B::BIT_WIDTH + j * B::ELEMENT_BIT_WIDTH + Self::select_in_element(!x, rank),
+                );
+            }
+            rank -= zeros;
+        }
+        unreachable!("rank/select directory is out of sync with the bitvec")
+    }
+}
+
+impl<B, const L: usize> From<BitVecSimd<B, L>> for RankSelect<B, L>
+where
+    B: BitBlock<L>,
+{
+    fn from(bitvec: BitVecSimd<B, L>) -> Self {
+        Self::new(bitvec)
+    }
+}
@@ -0,0 +1,170 @@
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use core::{
+    mem,
+    ops::{Deref, DerefMut},
+    slice,
+};
+
+use crate::{BitBlock, BitBlockElement, BitVecSimd};
+
+/// Mutable access to the storage elements of a [`BitVecSimd`].
+///
+/// Created by [`BitVecSimd::as_mut_elements`]. Bits at or above `len()` may be set through the
+/// guard, they are cleared when it is dropped.
+pub struct ElementsMut<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    bitvec: &'a mut BitVecSimd<B, L>,
+}
+
+impl<'a, B, const L: usize> Deref for ElementsMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Target = [B::Element];
+
+    fn deref(&self) -> &[B::Element] {
+        self.bitvec.as_elements()
+    }
+}
+
+impl<'a, B, const L: usize> DerefMut for ElementsMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn deref_mut(&mut self) -> &mut [B::Element] {
+        self.bitvec.elements_mut()
+    }
+}
+
+impl<'a, B, const L: usize> Drop for ElementsMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    fn drop(&mut self) {
+        self.bitvec.clear_tail();
+    }
+}
+
+impl<B, const L: usize> BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    #[inline]
+    pub(crate) fn check_layout() {
+        assert_eq!(mem::size_of::<B>(), mem::size_of::<[B::Element; L]>());
+        assert!(mem::align_of::<B>() >= mem::align_of::<B::Element>());
+    }
+
+    /// The storage as a slice of elements, including the zero elements after the last bit.
+    ///
+    /// Bit `i` is bit `i % ELEMENT_BIT_WIDTH` of element `i / ELEMENT_BIT_WIDTH`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 65]);
+    /// assert_eq!(bitvec.as_elements(), &[0b1010, 0b10, 0, 0]);
+    /// ```
+    pub fn as_elements(&self) -> &[B::Element] {
+        Self::check_layout();
+        // SAFETY: `B` has the layout of `[B::Element; L]` by the `BitBlock` contract
+        unsafe {
+            slice::from_raw_parts(
+                self.storage.as_ptr() as *const B::Element,
+                self.storage.len() * L,
+            )
+        }
+    }
+
+    // mutable elements without the guard, callers must keep bits at or above `len()` clear
+    pub(crate) fn elements_mut(&mut self) -> &mut [B::Element] {
+        Self::check_layout();
+        let storage = &mut self.storage;
+        // SAFETY: `B` has the layout of `[B::Element; L]` by the `BitBlock` contract
+        unsafe {
+            slice::from_raw_parts_mut(storage.as_mut_ptr() as *mut B::Element, storage.len() * L)
+        }
+    }
+
+    /// Mutable access to the storage elements, see [`BitVecSimd::as_elements`].
+    ///
+    /// Bits at or above `len()` are cleared when the returned guard is dropped.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::zeros(70);
+    /// {
+    ///     let mut elements = bitvec.as_mut_elements();
+    ///     elements[0] = 0b101;
+    ///     elements[1] = u64::MAX;
+    /// }
+    /// assert_eq!(bitvec.count_ones(), 2 + 6);
+    /// assert_eq!(bitvec.as_elements(), &[0b101, 0b111111, 0, 0]);
+    /// ```
+    pub fn as_mut_elements(&mut self) -> ElementsMut<'_, B, L> {
+        Self::check_layout();
+        ElementsMut { bitvec: self }
+    }
+
+    /// The storage as bytes, including the zero bytes after the last bit.
+    ///
+    /// Elements are in native byte order. On little-endian targets bit `i` is bit `i % 8` of
+    /// byte `i / 8`, the same order [`BitVecSimd::from_le_bytes`] reads.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[1, 3, 9]);
+    /// assert_eq!(bitvec.as_bytes().len(), 32);
+    /// if cfg!(target_endian = "little") {
+    ///     assert_eq!(bitvec.as_bytes()[..2], [0b1010, 0b10]);
+    ///     assert_eq!(BitVec::from_le_bytes(bitvec.as_bytes(), 10), bitvec);
+    /// }
+    /// ```
+    pub fn as_bytes(&self) -> &[u8] {
+        let elements = self.as_elements();
+        // SAFETY: elements are plain integers without padding by the `BitBlock` contract
+        unsafe { slice::from_raw_parts(elements.as_ptr() as *const u8, mem::size_of_val(elements)) }
+    }
+
+    /// Create a bitvec of `nbits` bits from `bytes`, where bit `i` is bit `i % 8` of byte `i / 8`.
+    ///
+    /// This is the raw `from_bytes(&[u8], nbits)` constructor. It is named `from_le_bytes` because
+    /// [`BitVecSimd::from_bytes`] already decodes the versioned binary format.
+    ///
+    /// Bits at or above `nbits` in `bytes` are ignored.
+    /// Panics if `bytes` holds fewer than `nbits` bits.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_le_bytes(&[0b1010, 0b11110010], 10);
+    /// assert_eq!(bitvec.len(), 10);
+    /// assert_eq!(bitvec.to_usizes(), vec![1, 3, 9]);
+    /// ```
+    pub fn from_le_bytes(bytes: &[u8], nbits: usize) -> Self {
+        let nbytes = nbits / 8 + (nbits % 8 != 0) as usize;
+        assert!(nbytes <= bytes.len());
+        let elements = bytes[..nbytes]
+            .chunks(B::ELEMENT_BIT_WIDTH / 8)
+            .map(B::Element::from_le_slice)
+            .collect::<Vec<_>>();
+        Self::from_slice_copy(&elements, nbits)
+    }
+}
@@ -0,0 +1,330 @@
+// Conversion from and to the portable Roaring bitmap serialization format, see
+// https://github.com/RoaringBitmap/RoaringFormatSpec
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::{vec, vec::Vec};
+
+use crate::{BitBlock, BitBlockElement, BitVecSimd, DecodeError};
+
+const SERIAL_COOKIE_NO_RUNCONTAINER: u32 = 12346;
+const SERIAL_COOKIE: u16 = 12347;
+// with run containers, the offset header is only written for this many containers or more
+const NO_OFFSET_THRESHOLD: usize = 4;
+// containers with more values are bitmaps unless they are runs
+const ARRAY_MAX_CARDINALITY: usize = 4096;
+const BITMAP_WORDS: usize = 1024;
+
+enum Container {
+    Array(Vec<u16>),
+    Bitmap(Vec<u64>),
+    // (start, length - 1)
+    Run(Vec<(u16, u16)>),
+}
+
+impl Container {
+    // the smallest container holding `values`, same choice as `runOptimize` of Roaring
+    fn new(values: Vec<u16>) -> Self {
+        let runs = values
+            .windows(2)
+            .filter(|w| w[0] as u32 + 1 != w[1] as u32)
+            .count()
+            + 1;
+        let run_bytes = 2 + 4 * runs;
+        let bytes = if values.len() <= ARRAY_MAX_CARDINALITY {
+            2 * values.len()
+        } else {
+            8 * BITMAP_WORDS
+        };
+        if run_bytes < bytes {
+            let mut ranges: Vec<(u16, u16)> = Vec::with_capacity(runs);
+            for v in values {
+                match ranges.last_mut() {
+                    Some((start, len)) if *start as u32 + *len as u32 + 1 == v as u32 => *len += 1,
+                    _ => ranges.push((v, 0)),
+                }
+            }
+            Container::Run(ranges)
+        } else if values.len() <= ARRAY_MAX_CARDINALITY {
+            Container::Array(values)
+        } else {
+            let mut words = vec![0u64; BITMAP_WORDS];
+            for v in values {
+                words[v as usize / 64] |= 1 << (v % 64);
+            }
+            Container::Bitmap(words)
+        }
+    }
+
+    fn serialized_len(&self) -> usize {
+        match self {
+            Container::Array(values) => 2 * values.len(),
+            Container::Bitmap(_) => 8 * BITMAP_WORDS,
+            Container::Run(runs) => 2 + 4 * runs.len(),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.bytes.len() < n {
+            return Err(DecodeError::UnexpectedEnd);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        let b = self.take(8)?;
+        let mut x = [0u8; 8];
+        x.copy_from_slice(b);
+        Ok(u64::from_le_bytes(x))
+    }
+}
+
+impl<B, const L: usize> BitVecSimd<B, L>
+where
+    B: BitBlock<L>,
+{
+    /// Serialize the indices of set bits in the portable Roaring format, readable by the
+    /// Roaring libraries of Java, Go, C and others as a 32-bit bitmap.
+    ///
+    /// Every container is written as an array, bitmap or run container, whichever is smallest.
+    /// The length of this bitvec isn't part of the format.
+    ///
+    /// Panics if the index of a set bit doesn't fit in a `u32`.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let mut bitvec = BitVec::from_slice(&[1, 70_000]);
+    /// bitvec.fill_range(100..200);
+    /// let bytes = bitvec.to_roaring();
+    /// assert_eq!(BitVec::from_roaring(&bytes).unwrap(), bitvec);
+    /// ```
+    pub fn to_roaring(&self) -> Vec<u8> {
+        let mut containers: Vec<(u16, usize, Container)> = Vec::new();
+        let mut key = None;
+        let mut values = Vec::new();
+        for index in self.usizes() {
+            let index = u32::try_from(index).expect("index of a set bit doesn't fit in u32");
+            let k = (index >> 16) as u16;
+            if key != Some(k) {
+                if let Some(key) = key {
+                    let values = core::mem::take(&mut values);
+                    containers.push((key, values.len(), Container::new(values)));
+                }
+                key = Some(k);
+            }
+            values.push(index as u16);
+        }
+        if let Some(key) = key {
+            containers.push((key, values.len(), Container::new(values)));
+        }
+
+        let size = containers.len();
+        let has_run = containers
+            .iter()
+            .any(|(_, _, c)| matches!(c, Container::Run(_)));
+        let mut out = Vec::new();
+        if has_run {
+            out.extend_from_slice(
+                &(SERIAL_COOKIE as u32 | ((size as u32 - 1) << 16)).to_le_bytes(),
+            );
+            let mut run_flags = vec![0u8; (size + 7) / 8];
+            for (i, (_, _, c)) in containers.iter().enumerate() {
+                if let Container::Run(_) = c {
+                    run_flags[i / 8] |= 1 << (i % 8);
+                }
+            }
+            out.extend_from_slice(&run_flags);
+        } else {
+            out.extend_from_slice(&SERIAL_COOKIE_NO_RUNCONTAINER.to_le_bytes());
+            out.extend_from_slice(&(size as u32).to_le_bytes());
+        }
+        for (key, cardinality, _) in containers.iter() {
+            out.extend_from_slice(&key.to_le_bytes());
+            out.extend_from_slice(&((cardinality - 1) as u16).to_le_bytes());
+        }
+        if !has_run || size >= NO_OFFSET_THRESHOLD {
+            let mut offset = out.len() + 4 * size;
+            for (_, _, c) in containers.iter() {
+                out.extend_from_slice(&(offset as u32).to_le_bytes());
+                offset += c.serialized_len();
+            }
+        }
+        for (_, _, c) in containers.iter() {
+            match c {
+                Container::Array(values) => {
+                    for v in values {
+                        out.extend_from_slice(&v.to_le_bytes());
+                    }
+                }
+                Container::Bitmap(words) => {
+                    for w in words {
+                        out.extend_from_slice(&w.to_le_bytes());
+                    }
+                }
+                Container::Run(runs) => {
+                    out.extend_from_slice(&(runs.len() as u16).to_le_bytes());
+                    for (start, len) in runs {
+                        out.extend_from_slice(&start.to_le_bytes());
+                        out.extend_from_slice(&len.to_le_bytes());
+                    }
+                }
+            }
+        }
+        out
+    }
+
+    /// Deserialize a 32-bit bitmap in the portable Roaring format.
+    ///
+    /// The length of the result is one past its last set bit.
+    /// Returns an error if the input is truncated, has trailing bytes, or if its containers are
+    /// out of order or inconsistent with their cardinality.
+    pub fn from_roaring(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader { bytes };
+        let cookie = r.u32()?;
+        let (size, run_flags) = if cookie == SERIAL_COOKIE_NO_RUNCONTAINER {
+            (r.u32()? as usize, &[][..])
+        } else if cookie as u16 == SERIAL_COOKIE {
+            let size = (cookie >> 16) as usize + 1;
+            (size, r.take((size + 7) / 8)?)
+        } else {
+            return Err(DecodeError::BadMagic);
+        };
+        if size > 1 << 16 {
+            return Err(DecodeError::InvalidContainer(0));
+        }
+        let header = r.take(4 * size)?;
+        if run_flags.is_empty() || size >= NO_OFFSET_THRESHOLD {
+            // containers are stored back to back, so the offsets aren't needed
+            r.take(4 * size)?;
+        }
+
+        let mut containers = Vec::with_capacity(size);
+        for i in 0..size {
+            let key = u16::from_le_bytes([header[4 * i], header[4 * i + 1]]);
+            let cardinality =
+                u16::from_le_bytes([header[4 * i + 2], header[4 * i + 3]]) as usize + 1;
+            if containers.last().map_or(false, |(k, _)| *k >= key) {
+                return Err(DecodeError::InvalidContainer(key));
+            }
+            let is_run = run_flags
+                .get(i / 8)
+                .map_or(false, |f| f & (1 << (i % 8)) != 0);
+            let container = if is_run {
+                let n = r.u16()? as usize;
+                let mut runs = Vec::with_capacity(n);
+                let mut count = 0;
+                for _ in 0..n {
+                    let (start, len) = (r.u16()?, r.u16()?);
+                    if start as u32 + len as u32 > u16::MAX as u32
+                        || runs.last().map_or(false, |(s, l): &(u16, u16)| {
+                            *s as u32 + *l as u32 >= start as u32
+                        })
+                    {
+                        return Err(DecodeError::InvalidContainer(key));
+                    }
+                    count += len as usize + 1;
+                    runs.push((start, len));
+                }
+                if count != cardinality {
+                    return Err(DecodeError::InvalidContainer(key));
+                }
+                Container::Run(runs)
+            } else if cardinality <= ARRAY_MAX_CARDINALITY {
+                let mut values = Vec::with_capacity(cardinality);
+                for _ in 0..cardinality {
+                    let v = r.u16()?;
+                    if values.last().map_or(false, |last| *last >= v) {
+                        return Err(DecodeError::InvalidContainer(key));
+                    }
+                    values.push(v);
+                }
+                Container::Array(values)
+            } else {
+                let mut words = Vec::with_capacity(BITMAP_WORDS);
+                for _ in 0..BITMAP_WORDS {
+                    words.push(r.u64()?);
+                }
+                if words.iter().map(|w| w.count_ones() as usize).sum::<usize>() != cardinality {
+                    return Err(DecodeError::InvalidContainer(key));
+                }
+                Container::Bitmap(words)
+            };
+            containers.push((key, container));
+        }
+        if !r.bytes.is_empty() {
+            return Err(DecodeError::TrailingBytes);
+        }
+
+        let nbits = containers.last().map_or(0, |(key, c)| {
+            let last = match c {
+                Container::Array(values) => *values.last().unwrap() as usize,
+                Container::Bitmap(words) => {
+                    let i = words.iter().rposition(|w| *w != 0).unwrap();
+                    i * 64 + 63 - words[i].leading_zeros() as usize
+                }
+                Container::Run(runs) => {
+                    let (start, len) = runs.last().unwrap();
+                    *start as usize + *len as usize
+                }
+            };
+            ((*key as usize) << 16) + last + 1
+        });
+        let mut bitvec = Self::zeros(nbits);
+        for (key, c) in containers {
+            let base = (key as usize) << 16;
+            match c {
+                Container::Array(values) => {
+                    bitvec.set_indices(values.into_iter().map(|v| base + v as usize), false);
+                }
+                Container::Bitmap(words) => {
+                    // a container starts at a multiple of 65536 bits, so it fills whole elements,
+                    // and nothing is set beyond the last bit of the last container
+                    let width = B::ELEMENT_BIT_WIDTH / 8;
+                    let mut bytes = words.into_iter().flat_map(u64::to_le_bytes);
+                    for e in &mut bitvec.elements_mut()[base / B::ELEMENT_BIT_WIDTH..] {
+                        let mut buf = [0u8; 16];
+                        let n = buf[..width]
+                            .iter_mut()
+                            .zip(&mut bytes)
+                            .map(|(b, x)| *b = x)
+                            .count();
+                        if n == 0 {
+                            break;
+                        }
+                        *e = B::Element::from_le_slice(&buf[..width]);
+                    }
+                }
+                Container::Run(runs) => {
+                    for (start, len) in runs {
+                        let start = base + start as usize;
+                        bitvec.fill_range(start..=start + len as usize);
+                    }
+                }
+            }
+        }
+        Ok(bitvec)
+    }
+}
@@ -0,0 +1,161 @@
+//! A compressed serde representation of [`BitVecSimd`].
+//!
+//! Use it on a field with `#[serde(with = "bitvec_simd::serde_compressed")]`:
+//!
+//! ```rust
+//! use bitvec_simd::BitVec;
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize)]
+//! struct Index {
+//!     #[serde(with = "bitvec_simd::serde_compressed")]
+//!     docs: BitVec,
+//! }
+//! ```
+//!
+//! Depending on the content, the bitvec is written as one of:
+//!
+//! * `dense`: `nbits` and the storage elements, without trailing zero elements.
+//! * `sparse`: `nbits` and the sorted indices of set bits.
+//! * `runs`: `nbits` and `(start, len)` pairs of consecutive set bits.
+//!
+//! The representation with the fewest bits is chosen, counting `ELEMENT_BIT_WIDTH` bits per
+//! element of `dense` and 64 bits per index of `sparse` and `runs`. The choice is stored in the
+//! output, so deserialization works whatever representation was picked.
+
+#[cfg(feature = "std")]
+use std::vec::Vec;
+
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+use serde::{de::Error, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{from_elements, try_zeros, BitBlock, BitBlockElement, BitVecSimd};
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename = "BitVecSimd", rename_all = "lowercase")]
+enum Repr<E> {
+    Dense {
+        nbits: usize,
+        elements: Vec<E>,
+    },
+    Sparse {
+        nbits: usize,
+        ones: Vec<usize>,
+    },
+    Runs {
+        nbits: usize,
+        runs: Vec<(usize, usize)>,
+    },
+}
+
+// number of runs of consecutive set bits
+fn count_runs<B: BitBlock<L>, const L: usize>(bitvec: &BitVecSimd<B, L>) -> usize {
+    let mut carry = B::ZERO_ELEMENT;
+    let mut runs = 0;
+    for block in bitvec.storage.iter() {
+        for x in block.to_array() {
+            // a run starts at every set bit whose lower neighbour is unset
+            let starts = x & !(x.wrapping_shl(1) | carry);
+            runs += starts.count_ones() as usize;
+            carry = x.wrapping_shr(B::ELEMENT_BIT_WIDTH as u32 - 1);
+        }
+    }
+    runs
+}
+
+/// Serialize `bitvec` with the most compact of the `dense`, `sparse` and `runs` representations.
+pub fn serialize<S, B, const L: usize>(bitvec: &BitVecSimd<B, L>, s: S) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    B: BitBlock<L>,
+{
+    let nbits = bitvec.nbits;
+    // elements up to the last non-zero one
+    let dense_len = bitvec
+        .last_one()
+        .map_or(0, |i| i / B::ELEMENT_BIT_WIDTH + 1);
+    let ones = bitvec.count_ones();
+    let runs = count_runs(bitvec);
+
+    let dense_cost = dense_len * B::ELEMENT_BIT_WIDTH;
+    let sparse_cost = ones * 64;
+    let runs_cost = runs * 2 * 64;
+    let repr = if dense_cost <= sparse_cost && dense_cost <= runs_cost {
+        Repr::Dense {
+            nbits,
+            elements: bitvec
+                .storage
+                .iter()
+                .flat_map(|x| x.to_array())
+                .take(dense_len)
+                .collect(),
+        }
+    } else if sparse_cost <= runs_cost {
+        Repr::Sparse {
+            nbits,
+            ones: bitvec.usizes().collect(),
+        }
+    } else {
+        let mut ranges = Vec::with_capacity(runs);
+        let mut start = bitvec.first_one();
+        while let Some(s) = start {
+            let end = bitvec.next_zero(s).unwrap_or(nbits);
+            ranges.push((s, end - s));
+            start = bitvec.next_one(end);
+        }
+        Repr::Runs {
+            nbits,
+            runs: ranges,
+        }
+    };
+    repr.serialize(s)
+}
+
+/// Deserialize a bitvec written by [`serialize`].
+///
+/// Returns an error if any set bit lies beyond `nbits`, if the indices of a `sparse`
+/// representation are not strictly increasing, or if `nbits` is too large to allocate.
+pub fn deserialize<'de, D, B, const L: usize>(deserializer: D) -> Result<BitVecSimd<B, L>, D::Error>
+where
+    D: Deserializer<'de>,
+    B: BitBlock<L>,
+{
+    match Repr::<B::Element>::deserialize(deserializer)? {
+        Repr::Dense { nbits, elements } => from_elements(elements, nbits),
+        Repr::Sparse { nbits, ones } => {
+            let mut bitvec = try_zeros(nbits)?;
+            let mut next = 0;
+            for index in ones {
+                if index < next {
+                    return Err(D::Error::custom("indices are not strictly increasing"));
+                }
+                if index >= nbits {
+                    return Err(D::Error::custom(format_args!(
+                        "index {} out of bounds for nbits {}",
+                        index, nbits
+                    )));
+                }
+                bitvec.set(index, true);
+                next = index + 1;
+            }
+            Ok(bitvec)
+        }
+        Repr::Runs { nbits, runs } => {
+            let mut bitvec = try_zeros(nbits)?;
+            for (start, len) in runs {
+                match start.checked_add(len) {
+                    Some(end) if end <= nbits => bitvec.fill_range(start..end),
+                    _ => {
+                        return Err(D::Error::custom(format_args!(
+                            "run {}+{} out of bounds for nbits {}",
+                            start, len, nbits
+                        )))
+                    }
+                }
+            }
+            Ok(bitvec)
+        }
+    }
+}
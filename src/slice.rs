@@ -0,0 +1,341 @@
+use core::{cmp, iter::FusedIterator, ops::RangeBounds};
+
+use crate::{range_to_bounds, BitBlock, BitBlockElement, BitVecSimd, Ones};
+
+/// An immutable view of bits `start..end` of a [`BitVecSimd`].
+///
+/// Created by [`BitVecSimd::slice`]. Indices passed to a slice are relative to its start.
+#[derive(Debug, Clone, Copy)]
+pub struct BitSlice<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    pub(crate) bitvec: &'a BitVecSimd<B, L>,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+/// A mutable view of bits `start..end` of a [`BitVecSimd`].
+///
+/// Created by [`BitVecSimd::slice_mut`]. Indices passed to a slice are relative to its start.
+#[derive(Debug)]
+pub struct BitSliceMut<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    bitvec: &'a mut BitVecSimd<B, L>,
+    start: usize,
+    end: usize,
+}
+
+macro_rules! impl_slice_operation {
+    ($name:ident, $op:tt) => {
+        /// Panics if the lengths of the two slices aren't the same.
+        pub fn $name(&self, other: &BitSlice<'_, B, L>) -> BitVecSimd<B, L> {
+            assert_eq!(self.len(), other.len());
+            let mut bitvec = self.to_bitvec();
+            let src = other.bitvec.as_elements();
+            bitvec.combine_bits(0, src, other.start, other.len(), |x, y| x $op y);
+            bitvec
+        }
+    };
+}
+
+macro_rules! impl_slice_operation_inplace {
+    ($name_inplace:ident, $op:tt) => {
+        /// Panics if the lengths of the two slices aren't the same.
+        pub fn $name_inplace(&mut self, other: &BitSlice<'_, B, L>) {
+            assert_eq!(self.len(), other.len());
+            let src = other.bitvec.as_elements();
+            self.bitvec
+                .combine_bits(self.start, src, other.start, other.len(), |x, y| x $op y);
+        }
+    };
+}
+
+impl<'a, B, const L: usize> BitSlice<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    pub(crate) fn new(bitvec: &'a BitVecSimd<B, L>, start: usize, end: usize) -> Self {
+        Self { bitvec, start, end }
+    }
+
+    /// Length of this slice.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return true if this slice has no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Same as [`BitVecSimd::get`], relative to the start of this slice.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        if index < self.len() {
+            self.bitvec.get(self.start + index)
+        } else {
+            None
+        }
+    }
+
+    /// Same as [`BitVecSimd::get_unchecked`], relative to the start of this slice.
+    pub fn get_unchecked(&self, index: usize) -> bool {
+        if index >= self.len() {
+            panic!("index out of bounds {} > {}", index, self.len());
+        }
+        self.bitvec.get_unchecked(self.start + index)
+    }
+
+    /// Count the number of ones in this slice.
+    pub fn count_ones(&self) -> usize {
+        self.ones().len()
+    }
+
+    #[inline]
+    fn ones(&self) -> Ones<'a, B, L> {
+        Ones::range(&self.bitvec.storage, self.start, self.end)
+    }
+
+    /// Return an iterator over every bit of this slice.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + 'a {
+        let (start, end) = (self.start, self.end);
+        let width = B::ELEMENT_BIT_WIDTH;
+        let elements = self.bitvec.as_elements();
+        // load every element once and test its bits in turn
+        (start / width..end / width + (end % width != 0) as usize).flat_map(move |i| {
+            let word = elements[i];
+            let from = start.saturating_sub(i * width);
+            let to = cmp::min(end - i * width, width);
+            (from..to)
+                .map(move |bit| word.wrapping_shr(bit as u32) & B::ONE_ELEMENT != B::ZERO_ELEMENT)
+        })
+    }
+
+    /// Return an iterator over the indices (relative to the start of this slice) of set bits.
+    pub fn usizes(&self) -> impl Iterator<Item = usize> + 'a {
+        let start = self.start;
+        self.ones().map(move |i| i - start)
+    }
+
+    /// Borrow `range` of this slice.
+    /// Panics if the range is out of bounds.
+    pub fn slice<R: RangeBounds<usize>>(&self, range: R) -> BitSlice<'a, B, L> {
+        let (start, end) = range_to_bounds(range, self.len());
+        Self::new(self.bitvec, self.start + start, self.start + end)
+    }
+
+    /// Copy this slice into a new bitvec starting from bit 0.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use bitvec_simd::BitVec;
+    ///
+    /// let bitvec = BitVec::from_slice(&[3, 70, 300]);
+    /// let bitvec2 = bitvec.slice(3..301).to_bitvec();
+    /// assert_eq!(bitvec2.len(), 298);
+    /// assert_eq!(bitvec2.to_usizes(), vec![0, 67, 297]);
+    /// ```
+    pub fn to_bitvec(&self) -> BitVecSimd<B, L> {
+        self.bitvec.extract(self.start, self.len())
+    }
+
+    impl_slice_operation!(and, &);
+    impl_slice_operation!(or, |);
+    impl_slice_operation!(xor, ^);
+}
+
+impl<'a, B, const L: usize> BitSliceMut<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    pub(crate) fn new(bitvec: &'a mut BitVecSimd<B, L>, start: usize, end: usize) -> Self {
+        Self { bitvec, start, end }
+    }
+
+    /// Reborrow as an immutable slice.
+    #[inline]
+    pub fn as_slice(&self) -> BitSlice<'_, B, L> {
+        BitSlice::new(self.bitvec, self.start, self.end)
+    }
+
+    /// Length of this slice.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Return true if this slice has no bits.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Same as [`BitVecSimd::get`], relative to the start of this slice.
+    pub fn get(&self, index: usize) -> Option<bool> {
+        self.as_slice().get(index)
+    }
+
+    /// Same as [`BitVecSimd::get_unchecked`], relative to the start of this slice.
+    pub fn get_unchecked(&self, index: usize) -> bool {
+        self.as_slice().get_unchecked(index)
+    }
+
+    /// Set bit `index` (relative to the start of this slice) to `flag`.
+    /// Unlike [`BitVecSimd::set`], a slice never grows, so this panics if index >= len.
+    pub fn set(&mut self, index: usize, flag: bool) {
+        if index >= self.len() {
+            panic!("index out of bounds {} > {}", index, self.len());
+        }
+        self.bitvec.set(self.start + index, flag);
+    }
+
+    /// Count the number of ones in this slice.
+    pub fn count_ones(&self) -> usize {
+        self.as_slice().count_ones()
+    }
+
+    /// Return an iterator over every bit of this slice.
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        self.as_slice().iter()
+    }
+
+    /// Return an iterator over the indices (relative to the start of this slice) of set bits.
+    pub fn usizes(&self) -> impl Iterator<Item = usize> + '_ {
+        self.as_slice().usizes()
+    }
+
+    /// Copy this slice into a new bitvec starting from bit 0.
+    pub fn to_bitvec(&self) -> BitVecSimd<B, L> {
+        self.as_slice().to_bitvec()
+    }
+
+    /// Overwrite this slice with the content of `other`.
+    /// Panics if the lengths of the two slices aren't the same.
+    pub fn copy_from_slice(&mut self, other: &BitSlice<'_, B, L>) {
+        assert_eq!(self.len(), other.len());
+        let src = other.bitvec.as_elements();
+        self.bitvec
+            .combine_bits(self.start, src, other.start, other.len(), |_, y| y);
+    }
+
+    impl_slice_operation!(and, &);
+    impl_slice_operation!(or, |);
+    impl_slice_operation!(xor, ^);
+    impl_slice_operation_inplace!(and_inplace, &);
+    impl_slice_operation_inplace!(or_inplace, |);
+    impl_slice_operation_inplace!(xor_inplace, ^);
+}
+
+/// Iterator over consecutive [`BitSlice`]s of a fixed length, the last one may be shorter.
+///
+/// Created by [`BitVecSimd::chunks`].
+#[derive(Debug, Clone)]
+pub struct Chunks<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    bitvec: &'a BitVecSimd<B, L>,
+    start: usize,
+    end: usize,
+    size: usize,
+}
+
+impl<'a, B, const L: usize> Chunks<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    pub(crate) fn new(bitvec: &'a BitVecSimd<B, L>, size: usize) -> Self {
+        assert!(size > 0, "chunk size must be non-zero");
+        Self {
+            bitvec,
+            start: 0,
+            end: bitvec.len(),
+            size,
+        }
+    }
+}
+
+impl<'a, B, const L: usize> Iterator for Chunks<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Item = BitSlice<'a, B, L>;
+
+    fn next(&mut self) -> Option<BitSlice<'a, B, L>> {
+        if self.start == self.end {
+            return None;
+        }
+        let end = self.end.min(self.start + self.size);
+        let chunk = BitSlice::new(self.bitvec, self.start, end);
+        self.start = end;
+        Some(chunk)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = self.end - self.start;
+        let n = n / self.size + (n % self.size != 0) as usize;
+        (n, Some(n))
+    }
+}
+
+impl<'a, B, const L: usize> ExactSizeIterator for Chunks<'a, B, L> where B: BitBlock<L> {}
+
+impl<'a, B, const L: usize> FusedIterator for Chunks<'a, B, L> where B: BitBlock<L> {}
+
+/// Iterator over consecutive [`BitSlice`]s of exactly the same length.
+///
+/// Created by [`BitVecSimd::chunks_exact`]. The bits left over at the end are available from
+/// [`ChunksExact::remainder`].
+#[derive(Debug, Clone)]
+pub struct ChunksExact<'a, B, const L: usize>
+where
+    B: BitBlock<L>,
+{
+    chunks: Chunks<'a, B, L>,
+    remainder: BitSlice<'a, B, L>,
+}
+
+impl<'a, B, const L: usize> ChunksExact<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    pub(crate) fn new(bitvec: &'a BitVecSimd<B, L>, size: usize) -> Self {
+        let mut chunks = Chunks::new(bitvec, size);
+        chunks.end -= bitvec.len() % size;
+        let remainder = BitSlice::new(bitvec, chunks.end, bitvec.len());
+        Self { chunks, remainder }
+    }
+
+    /// The last bits which don't fill a whole chunk.
+    #[inline]
+    pub fn remainder(&self) -> BitSlice<'a, B, L> {
+        self.remainder
+    }
+}
+
+impl<'a, B, const L: usize> Iterator for ChunksExact<'a, B, L>
+where
+    B: BitBlock<L>,
+{
+    type Item = BitSlice<'a, B, L>;
+
+    #[inline]
+    fn next(&mut self) -> Option<BitSlice<'a, B, L>> {
+        self.chunks.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl<'a, B, const L: usize> ExactSizeIterator for ChunksExact<'a, B, L> where B: BitBlock<L> {}
+
+impl<'a, B, const L: usize> FusedIterator for ChunksExact<'a, B, L> where B: BitBlock<L> {}
@@ -1,5 +1,38 @@
 use crate::*;
 
+/// Runs a generic `check::<B, L>(args..)` helper against every block type.
+macro_rules! for_each_block {
+    ($check:ident $(, $arg:expr)*) => {
+        $check::<u8x16, 16>($($arg),*);
+        $check::<u16x8, 8>($($arg),*);
+        $check::<u32x4, 4>($($arg),*);
+        $check::<u32x8, 8>($($arg),*);
+        $check::<u64x2, 2>($($arg),*);
+        $check::<u64x4, 4>($($arg),*);
+        $check::<u8x32, 32>($($arg),*);
+        $check::<u16x16, 16>($($arg),*);
+        $check::<u64x4x2, 8>($($arg),*);
+        $check::<u64, 1>($($arg),*);
+        $check::<u128, 1>($($arg),*);
+    };
+}
+
+/// Deterministic xorshift64 generator for randomized tests.
+struct Rng(u64);
+
+impl Rng {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.next_u64() as usize % n
+    }
+}
+
 #[test]
 fn test_bit_to_len() {
     type T = BitVecSimd<u64x4, 4>;
@@ -26,7 +59,6 @@ fn test_bit_vec_count_ones() {
     assert_eq!(bitvec.count_ones_before(500), 500);
 }
 
-
 #[test]
 fn test_bit_vec_count_ones_before_big() {
     for sz in 0..=512 {
@@ -87,10 +119,10 @@ fn test_bit_vec_count_ones_before_small() {
     assert_eq!(bitvec.count_ones_before(4), 4);
 
     // from the example:
-    let bitvec: BitVec = (0..10_000).map(|x| x % 2 == 0).into();
+    let bitvec: BitVec = (0..10_000).map(|x| x % 2 == 0).collect();
     assert_eq!(bitvec.count_ones_before(5000), 2500);
 
-    let bitvec: BitVec = (0..20_000).map(|x| x % 3 == 0).into();
+    let bitvec: BitVec = (0..20_000).map(|x| x % 3 == 0).collect();
     assert_eq!(bitvec.count_ones_before(10000), 3334);
 }
 
@@ -362,7 +394,7 @@ fn test_bitvec_creation() {
     assert_eq!(bitvec.get(63), Some(false));
     assert_eq!(bitvec.get(64), None);
 
-    let v = vec![7];
+    let v = [7];
     let buf = v.as_ptr();
     let bitvec = unsafe { BitVec::from_raw_copy(buf, 1, 2) };
     assert_eq!(v.len(), 1); // ensure v lives long enough
@@ -370,7 +402,7 @@ fn test_bitvec_creation() {
     assert_eq!(bitvec.get(1), Some(true));
     assert_eq!(bitvec.get(2), None);
 
-    let v = vec![7];
+    let v = [7];
     let buf = v.as_ptr();
     let bitvec = unsafe { BitVec::from_raw_copy(buf, 1, 64) };
     assert_eq!(v.len(), 1); // ensure v lives long enough
@@ -384,7 +416,7 @@ fn test_bitvec_creation() {
 
 #[test]
 fn test_bitvec_set_raw_copy() {
-    let v = vec![7];
+    let v = [7];
     let buf = v.as_ptr();
     let mut bitvec = unsafe { BitVec::from_raw_copy(buf, 1, 64) };
     let ptr = bitvec.storage.as_mut_ptr();
@@ -484,7 +516,7 @@ fn test_ser_de() {
         ],
     );
 
-    type BitVec32 = BitVecSimd<[u32x8; 4], 8>;
+    type BitVec32 = BitVecSimd<u32x8, 8>;
 
     let bitvec = BitVec32::ones(66);
     serde_test::assert_tokens(
@@ -506,3 +538,1375 @@ fn test_ser_de() {
         ],
     );
 }
+
+#[test]
+fn test_bitvec_usizes() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 63, 64, 65, 127, 128, 129, 1000, 5000] {
+            let bools = (0..nbits)
+                .map(|x| x % 7 == 0 || x % 61 == 3 || (x > 700 && x < 900))
+                .collect::<Vec<_>>();
+            let expected = (0..nbits).filter(|x| bools[*x]).collect::<Vec<_>>();
+            let bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.into_iter());
+            assert_eq!(bitvec.usizes().len(), expected.len());
+            assert_eq!(bitvec.usizes().collect::<Vec<_>>(), expected);
+            assert_eq!(
+                bitvec.usizes().rev().collect::<Vec<_>>(),
+                expected.iter().rev().cloned().collect::<Vec<_>>()
+            );
+
+            // alternate between both ends
+            let mut iter = bitvec.usizes();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            while let Some(x) = iter.next() {
+                front.push(x);
+                match iter.next_back() {
+                    Some(x) => back.push(x),
+                    None => break,
+                }
+                assert_eq!(iter.len(), expected.len() - front.len() - back.len());
+            }
+            assert_eq!(iter.next(), None);
+            assert_eq!(iter.next_back(), None);
+            front.extend(back.into_iter().rev());
+            assert_eq!(front, expected);
+        }
+    }
+    for_each_block!(check);
+
+    // sparse bitvec
+    let mut bitvec = BitVec::zeros(10_000_000);
+    bitvec.set(3, true);
+    bitvec.set(5_000_000, true);
+    bitvec.set(9_999_999, true);
+    assert_eq!(
+        bitvec.usizes().collect::<Vec<_>>(),
+        vec![3, 5_000_000, 9_999_999]
+    );
+    assert_eq!(
+        bitvec.usizes().rev().collect::<Vec<_>>(),
+        vec![9_999_999, 5_000_000, 3]
+    );
+}
+
+#[test]
+fn test_rank_select() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 64, 65, 1000, 20_000] {
+            let bitvec = BitVecSimd::<B, L>::from_bool_iterator(
+                (0..nbits).map(|x| x % 5 == 0 || (x > 300 && x < 9000 && x % 13 != 0)),
+            );
+            let ones = bitvec.usizes().collect::<Vec<_>>();
+            let zeros = (0..nbits)
+                .filter(|x| !bitvec.get_unchecked(*x))
+                .collect::<Vec<_>>();
+            let rs = RankSelect::new(bitvec.clone());
+            assert_eq!(rs.count_ones(), ones.len());
+            for i in (0..=nbits).step_by(7).chain([nbits]) {
+                assert_eq!(rs.rank1(i), bitvec.count_ones_before(i));
+                assert_eq!(rs.rank0(i), i - bitvec.count_ones_before(i));
+            }
+            for (rank, index) in ones.iter().enumerate() {
+                assert_eq!(rs.select1(rank), Some(*index));
+            }
+            assert_eq!(rs.select1(ones.len()), None);
+            for (rank, index) in zeros.iter().enumerate() {
+                assert_eq!(rs.select0(rank), Some(*index));
+            }
+            assert_eq!(rs.select0(zeros.len()), None);
+        }
+    }
+    for_each_block!(check);
+
+    let mut rs = RankSelect::new(BitVec::zeros(100_000));
+    assert_eq!(rs.select1(0), None);
+    rs.update(|bitvec| bitvec.set(99_999, true));
+    assert_eq!(rs.select1(0), Some(99_999));
+    assert_eq!(rs.rank1(100_000), 1);
+    assert_eq!(rs.into_inner().count_ones(), 1);
+}
+
+#[test]
+fn test_bitvec_shift() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 63, 64, 65, 300, 1000] {
+            let bools = (0..nbits)
+                .map(|x| x % 3 == 0 || x % 17 == 1)
+                .collect::<Vec<_>>();
+            let bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.iter().cloned());
+            for n in [
+                0, 1, 7, 8, 31, 64, 65, 127, 128, 129, 256, 299, 999, 1000, 2000,
+            ] {
+                let shl = (0..nbits).map(|i| i >= n && bools[i - n]);
+                let shl = BitVecSimd::<B, L>::from_bool_iterator(shl);
+                let shr = (0..nbits).map(|i| i + n < nbits && bools[i + n]);
+                let shr = BitVecSimd::<B, L>::from_bool_iterator(shr);
+
+                assert_eq!(&bitvec << n, shl);
+                assert_eq!(&bitvec >> n, shr);
+                assert_eq!(bitvec.clone() << n, shl);
+                assert_eq!(bitvec.clone() >> n, shr);
+                let mut b = bitvec.clone();
+                b <<= n;
+                assert_eq!(b.count_ones(), shl.count_ones());
+                let mut b = bitvec.clone();
+                b >>= n;
+                assert_eq!(b.count_ones(), shr.count_ones());
+
+                let mut b = bitvec.clone();
+                b.shl_grow(n);
+                assert_eq!(b.len(), nbits + n);
+                assert_eq!(
+                    b.to_usizes(),
+                    bitvec.usizes().map(|i| i + n).collect::<Vec<_>>()
+                );
+                let mut b = bitvec.clone();
+                b.shr_shrink(n);
+                assert_eq!(b.len(), nbits.saturating_sub(n));
+                assert_eq!(
+                    b.to_usizes(),
+                    bitvec
+                        .usizes()
+                        .filter(|i| *i >= n)
+                        .map(|i| i - n)
+                        .collect::<Vec<_>>()
+                );
+            }
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_bitvec_slice() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let nbits = 1000;
+        let bools = (0..nbits)
+            .map(|x| x % 3 == 0 || x % 11 == 5)
+            .collect::<Vec<_>>();
+        let bools2 = (0..nbits).map(|x| x % 7 < 3).collect::<Vec<_>>();
+        let bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.iter().cloned());
+        let bitvec2 = BitVecSimd::<B, L>::from_bool_iterator(bools2.iter().cloned());
+        for (start, end) in [
+            (0, 0),
+            (0, 1000),
+            (1, 64),
+            (63, 65),
+            (100, 357),
+            (129, 1000),
+        ] {
+            let len = end - start;
+            let slice = bitvec.slice(start..end);
+            assert_eq!(slice.len(), len);
+            assert_eq!(slice.iter().collect::<Vec<_>>(), bools[start..end]);
+            assert_eq!(slice.get(len), None);
+            assert_eq!(
+                slice.count_ones(),
+                bools[start..end].iter().filter(|x| **x).count()
+            );
+            let materialized = slice.to_bitvec();
+            assert_eq!(materialized.len(), len);
+            assert_eq!(materialized.clone().into_bools(), bools[start..end]);
+            assert_eq!(
+                slice.usizes().collect::<Vec<_>>(),
+                materialized.clone().to_usizes()
+            );
+
+            // operate on unaligned ranges of both bitvecs
+            let other = bitvec2.slice(nbits - len..);
+            let expected = |f: fn(bool, bool) -> bool| {
+                (0..len)
+                    .map(|i| f(bools[start + i], bools2[nbits - len + i]))
+                    .collect::<Vec<_>>()
+            };
+            assert_eq!(slice.and(&other).into_bools(), expected(|a, b| a & b));
+            assert_eq!(slice.or(&other).into_bools(), expected(|a, b| a | b));
+            assert_eq!(slice.xor(&other).into_bools(), expected(|a, b| a ^ b));
+
+            let mut target = bitvec.clone();
+            target.slice_mut(start..end).xor_inplace(&other);
+            let mut result = bools.clone();
+            result.splice(start..end, expected(|a, b| a ^ b));
+            assert_eq!(target.count_ones(), result.iter().filter(|x| **x).count());
+            assert_eq!(target.into_bools(), result);
+
+            let mut target = BitVecSimd::<B, L>::ones(nbits);
+            let mut slice_mut = target.slice_mut(start..end);
+            slice_mut.and_inplace(&bitvec.slice(start..end));
+            slice_mut.or_inplace(&other);
+            if len > 0 {
+                slice_mut.set(0, false);
+            }
+            let mut result = vec![true; nbits];
+            result.splice(start..end, expected(|a, b| a | b));
+            if len > 0 {
+                result[start] = false;
+            }
+            assert_eq!(target.into_bools(), result);
+
+            let mut target = bitvec.clone();
+            target.slice_mut(start..end).copy_from_slice(&other);
+            let mut result = bools.clone();
+            result.splice(start..end, expected(|_, b| b));
+            assert_eq!(target.into_bools(), result);
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_slice_panic() {
+    let bitvec = BitVec::ones(100);
+    bitvec.slice(50..101);
+}
+
+#[test]
+fn test_bitvec_set_range() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let nbits = 700;
+        let bools = (0..nbits).map(|x| x % 3 == 0).collect::<Vec<_>>();
+        let ranges = [
+            (0, 0),
+            (5, 5),
+            (0, 700),
+            (3, 9),
+            (63, 65),
+            (64, 128),
+            (100, 600),
+            (129, 700),
+            (650, 900),
+            (700, 1025),
+        ];
+        for (start, end) in ranges {
+            let expected_len = if start < end { nbits.max(end) } else { nbits };
+            for op in 0..3 {
+                let mut bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.iter().cloned());
+                let mut expected = bools.clone();
+                expected.resize(expected_len, false);
+                for b in expected.iter_mut().take(end).skip(start) {
+                    *b = match op {
+                        0 => true,
+                        1 => false,
+                        _ => !*b,
+                    };
+                }
+                match op {
+                    0 => bitvec.set_range(start..end, true),
+                    1 => bitvec.clear_range(start..end),
+                    _ => bitvec.toggle_range(start..end),
+                }
+                assert_eq!(bitvec.len(), expected_len);
+                assert_eq!(bitvec.count_ones(), expected.iter().filter(|x| **x).count());
+                assert_eq!(bitvec.into_bools(), expected);
+            }
+        }
+    }
+    for_each_block!(check);
+
+    let mut bitvec = BitVec::zeros(100);
+    bitvec.fill_range(..);
+    assert!(bitvec.all());
+    bitvec.clear_range(10..);
+    assert_eq!(bitvec.count_ones(), 10);
+}
+
+#[test]
+#[should_panic(expected = "range bound overflow")]
+fn test_bitvec_set_range_overflow_panic() {
+    BitVec::zeros(10).fill_range(..=usize::MAX);
+}
+
+#[test]
+fn test_bitvec_mismatched_len() {
+    let a: BitVec = (0..300).map(|x| x % 2 == 0).collect();
+    let b: BitVec = (0..1000).map(|x| x % 3 == 0).collect();
+    let err = LengthMismatch {
+        left: 300,
+        right: 1000,
+    };
+    assert_eq!(a.try_and(&b), Err(err));
+    assert_eq!(a.try_or(&b), Err(err));
+    assert_eq!(a.try_xor(&b), Err(err));
+    let mut c = a.clone();
+    assert_eq!(c.try_xor_inplace(&b), Err(err));
+    assert_eq!(c, a);
+    assert_eq!(a.try_and(&a), Ok(a.clone()));
+    assert_eq!(c.try_or_inplace(&a), Ok(()));
+    assert_eq!(
+        format!("{}", err),
+        "length mismatch: left has 300 bits, right has 1000 bits"
+    );
+
+    type Op = fn(&BitVec, &BitVec, LengthPolicy) -> BitVec;
+    type BoolOp = fn(bool, bool) -> bool;
+    let ops: [(BoolOp, Op); 3] = [
+        (|x, y| x & y, BitVec::and_with),
+        (|x, y| x | y, BitVec::or_with),
+        (|x, y| x ^ y, BitVec::xor_with),
+    ];
+    for (f, op) in ops {
+        for (x, y) in [(&a, &b), (&b, &a)] {
+            let get = |v: &BitVec, i| v.get(i).unwrap_or(false);
+            let expected = BitVec::from_bool_iterator((0..1000).map(|i| f(get(x, i), get(y, i))));
+            assert_eq!(op(x, y, LengthPolicy::ZeroExtend), expected);
+            let expected = BitVec::from_bool_iterator((0..300).map(|i| f(get(x, i), get(y, i))));
+            let result = op(x, y, LengthPolicy::Truncate);
+            assert_eq!(result.count_ones(), expected.count_ones());
+            assert_eq!(result, expected);
+        }
+    }
+
+    let mut c = a.clone();
+    c.and_inplace_with(&b, LengthPolicy::ZeroExtend);
+    assert_eq!(c.len(), 1000);
+    assert_eq!(
+        c.to_usizes(),
+        (0..300).filter(|x| x % 6 == 0).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn test_bitvec_eq_hash_ord() {
+    use std::collections::{BTreeSet, HashSet};
+
+    assert_ne!(BitVec::zeros(10), BitVec::zeros(11));
+    assert_ne!(BitVec::ones(10), BitVec::ones(1000));
+
+    let bitvecs = (0..200)
+        .map(|n| BitVec::from_bool_iterator((0..n % 70 + n / 70).map(|x| (x * 7 + n) % 5 == 0)))
+        .collect::<Vec<_>>();
+    for a in bitvecs.iter() {
+        for b in bitvecs.iter() {
+            let (x, y) = (a.clone().into_bools(), b.clone().into_bools());
+            assert_eq!(a == b, x == y);
+            assert_eq!(a.cmp(b), x.cmp(&y), "{} {}", a, b);
+            assert_eq!(a.partial_cmp(b), x.partial_cmp(&y));
+        }
+    }
+
+    let hash_set = bitvecs.iter().cloned().collect::<HashSet<_>>();
+    let tree_set = bitvecs.iter().cloned().collect::<BTreeSet<_>>();
+    let mut bools = bitvecs
+        .iter()
+        .map(|x| x.clone().into_bools())
+        .collect::<Vec<_>>();
+    bools.sort();
+    bools.dedup();
+    assert_eq!(hash_set.len(), bools.len());
+    assert_eq!(
+        tree_set
+            .into_iter()
+            .map(|x| x.into_bools())
+            .collect::<Vec<_>>(),
+        bools
+    );
+
+    // the same set built in different ways hashes the same
+    let mut a = BitVec::ones(1000);
+    a.resize(300, false);
+    let b = BitVec::ones(300);
+    let set = [a].into_iter().collect::<HashSet<_>>();
+    assert!(set.contains(&b));
+}
+
+#[test]
+fn test_bitvec_set_comparison() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let sets = [
+            vec![],
+            vec![0],
+            vec![1, 3, 5],
+            vec![1, 3, 5, 700],
+            vec![0, 1, 2, 3, 4, 5, 6],
+            vec![2, 4, 6, 300],
+            vec![300, 301, 1000],
+        ];
+        for (i, x) in sets.iter().enumerate() {
+            for (j, y) in sets.iter().enumerate() {
+                // same sets with different lengths
+                for (xlen, ylen) in [(0, 0), (0, 500), (1500, 0), (1024, 1025)] {
+                    let mut a = BitVecSimd::<B, L>::from_slice(x);
+                    a.resize(a.len().max(xlen), false);
+                    let mut b = BitVecSimd::<B, L>::from_slice(y);
+                    b.resize(b.len().max(ylen), false);
+                    let and = x.iter().filter(|k| y.contains(k)).count();
+                    assert_eq!(a.is_subset(&b), and == x.len(), "{} {}", i, j);
+                    assert_eq!(a.is_superset(&b), and == y.len(), "{} {}", i, j);
+                    assert_eq!(a.is_disjoint(&b), and == 0, "{} {}", i, j);
+                    assert_eq!(a.and_count(&b), and);
+                    assert_eq!(a.or_count(&b), x.len() + y.len() - and);
+                    assert_eq!(a.xor_count(&b), x.len() + y.len() - 2 * and);
+                    assert_eq!(a.andnot_count(&b), x.len() - and);
+                }
+            }
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_bitvec_difference() {
+    let mut bitvec: BitVec = (0..1000).map(|x| x % 2 == 0).collect();
+    let mut bitvec2: BitVec = (0..1000).map(|x| x % 3 == 0).collect();
+    let expected: BitVec = (0..1000).map(|x| x % 2 == 0 && x % 3 != 0).collect();
+    assert_eq!(bitvec.difference_cloned(&bitvec2), expected);
+    assert_eq!(&bitvec - &bitvec2, expected);
+    assert_eq!((&mut bitvec) - &bitvec2, expected);
+    assert_eq!(&bitvec - (&mut bitvec2), expected);
+    assert_eq!((&mut bitvec) - (&mut bitvec2), expected);
+    assert_eq!(&bitvec - bitvec2.clone(), expected);
+    assert_eq!((&mut bitvec) - bitvec2.clone(), expected);
+    assert_eq!(bitvec.clone() - &bitvec2, expected);
+    assert_eq!(bitvec.clone() - (&mut bitvec2), expected);
+    assert_eq!(bitvec.clone() - bitvec2.clone(), expected);
+
+    let mut bitvec3 = bitvec.clone();
+    bitvec3.difference_inplace(&bitvec2);
+    assert_eq!(bitvec3, expected);
+    let mut bitvec3 = bitvec.clone();
+    bitvec3 -= bitvec2.clone();
+    assert_eq!(bitvec3, expected);
+    let mut bitvec3 = bitvec.clone();
+    bitvec3 -= &mut bitvec2;
+    assert_eq!(bitvec3, expected);
+
+    // the tail must stay 0
+    let bitvec = BitVec::zeros(1001);
+    assert_eq!((&bitvec - BitVec::zeros(1001)).count_ones(), 0);
+    assert_eq!(bitvec.difference(BitVec::ones(1001)), BitVec::zeros(1001));
+}
+
+#[test]
+fn test_bitvec_similarity() {
+    let a: BitVec = (0..10_000).map(|x| x % 2 == 0).collect();
+    let b: BitVec = (0..5_000).map(|x| x % 3 == 0).collect();
+    let (x, y) = (a.count_ones() as f64, b.count_ones() as f64);
+    let and = a.and_count(&b) as f64;
+    let or = a.or_count(&b) as f64;
+    assert_eq!(a.hamming_distance(&b), a.xor_count(&b));
+    assert_eq!(a.jaccard(&b), and / or);
+    assert_eq!(b.jaccard(&a), and / or);
+    assert_eq!(a.tanimoto(&b), and / or);
+    assert_eq!(a.dice(&b), 2.0 * and / (x + y));
+
+    assert_eq!(a.hamming_distance(&a), 0);
+    assert_eq!(a.jaccard(&a), 1.0);
+    assert_eq!(a.dice(&a), 1.0);
+
+    let empty = BitVec::zeros(100);
+    assert_eq!(empty.jaccard(&BitVec::zeros(0)), 1.0);
+    assert_eq!(empty.dice(&empty), 1.0);
+    assert_eq!(empty.jaccard(&a), 0.0);
+    assert_eq!(empty.dice(&a), 0.0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_bitvec_cosine() {
+    let a: BitVec = (0..10_000).map(|x| x % 2 == 0).collect();
+    let b: BitVec = (0..5_000).map(|x| x % 3 == 0).collect();
+    let (x, y) = (a.count_ones() as f64, b.count_ones() as f64);
+    let and = a.and_count(&b) as f64;
+    assert!((a.cosine(&b) - and / (x * y).sqrt()).abs() < 1e-12);
+    assert!((a.cosine(&a) - 1.0).abs() < 1e-12);
+
+    let empty = BitVec::zeros(100);
+    assert_eq!(empty.cosine(&empty), 1.0);
+    assert_eq!(empty.cosine(&a), 0.0);
+}
+
+#[test]
+fn test_bitvec_navigation() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 64, 65, 300, 1000, 2049] {
+            let patterns: [fn(usize) -> bool; 5] = [
+                |_| false,
+                |_| true,
+                |x| x == 0 || x == 700,
+                |x| !(x == 5 || (300..600).contains(&x)),
+                |x| (x > 100 && x < 130) || x % 97 == 96,
+            ];
+            for f in patterns {
+                let bools = (0..nbits).map(f).collect::<Vec<_>>();
+                let bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.iter().cloned());
+                let find = |from: usize, value: bool, forward: bool| {
+                    if forward {
+                        (from..nbits).find(|i| bools[*i] == value)
+                    } else if nbits == 0 {
+                        None
+                    } else {
+                        (0..=from.min(nbits - 1)).rev().find(|i| bools[*i] == value)
+                    }
+                };
+                assert_eq!(bitvec.first_one(), find(0, true, true));
+                assert_eq!(bitvec.first_zero(), find(0, false, true));
+                assert_eq!(bitvec.last_one(), find(usize::MAX, true, false));
+                assert_eq!(bitvec.last_zero(), find(usize::MAX, false, false));
+                assert_eq!(
+                    bitvec.trailing_zeros(),
+                    find(0, true, true).unwrap_or(nbits)
+                );
+                for from in (0..nbits + 10).step_by(13) {
+                    assert_eq!(bitvec.next_one(from), find(from, true, true));
+                    assert_eq!(bitvec.next_zero(from), find(from, false, true));
+                    assert_eq!(bitvec.prev_one(from), find(from, true, false));
+                    assert_eq!(bitvec.prev_zero(from), find(from, false, false));
+                }
+            }
+        }
+    }
+    for_each_block!(check);
+}
+
+#[cfg(feature = "use_serde")]
+#[test]
+fn test_ser_de_compressed() {
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Wrapper {
+        #[serde(with = "crate::serde_compressed")]
+        bits: BitVec,
+    }
+
+    let check = |bits: BitVec, expected: &str| {
+        let wrapper = Wrapper { bits };
+        let json = serde_json::to_string(&wrapper).unwrap();
+        assert!(json.contains(expected), "{}", json);
+        assert_eq!(serde_json::from_str::<Wrapper>(&json).unwrap(), wrapper);
+    };
+    check(BitVec::zeros(0), "dense");
+    check(
+        BitVec::zeros(100_000_000),
+        r#"{"dense":{"nbits":100000000,"elements":[]}}"#,
+    );
+    check(
+        BitVec::from_slice(&[3, 5, 99_999_999]),
+        r#"{"sparse":{"nbits":100000000,"ones":[3,5,99999999]}}"#,
+    );
+    check(
+        BitVec::ones(1000),
+        r#"{"runs":{"nbits":1000,"runs":[[0,1000]]}}"#,
+    );
+    check((0..1000).map(|x| x % 3 == 0).collect(), "dense");
+    let mut bits = BitVec::zeros(1_000_000);
+    bits.fill_range(100..200_000);
+    bits.fill_range(300_000..=300_063);
+    bits.set(999_999, true);
+    check(bits, r#""runs":[[100,199900],[300000,64],[999999,1]]"#);
+
+    let de = |json: &str| {
+        serde_json::from_str::<Wrapper>(json).map_err(|e| std::string::ToString::to_string(&e))
+    };
+    assert!(de(r#"{"bits":{"dense":{"nbits":3,"elements":[8]}}}"#)
+        .unwrap_err()
+        .contains("set bits beyond nbits 3"));
+    assert!(de(r#"{"bits":{"dense":{"nbits":3,"elements":[1,0]}}}"#)
+        .unwrap_err()
+        .contains("2 elements are too many for 3 bits"));
+    assert!(de(r#"{"bits":{"sparse":{"nbits":3,"ones":[2,1]}}}"#)
+        .unwrap_err()
+        .contains("not strictly increasing"));
+    assert!(de(r#"{"bits":{"sparse":{"nbits":3,"ones":[3]}}}"#)
+        .unwrap_err()
+        .contains("index 3 out of bounds"));
+    assert!(de(r#"{"bits":{"runs":{"nbits":3,"runs":[[1,3]]}}}"#)
+        .unwrap_err()
+        .contains("run 1+3 out of bounds"));
+    // nbits isn't trusted: no overflow, no allocation abort
+    for repr in ["dense", "sparse", "runs"] {
+        let field = match repr {
+            "dense" => "elements",
+            "sparse" => "ones",
+            _ => "runs",
+        };
+        for nbits in ["18446744073709551615", "1000000000000000"] {
+            let json = format!(
+                r#"{{"bits":{{"{}":{{"nbits":{},"{}":[]}}}}}}"#,
+                repr, nbits, field
+            );
+            assert!(de(&json).unwrap_err().contains("too large to allocate"));
+        }
+    }
+    assert_eq!(
+        de(r#"{"bits":{"dense":{"nbits":3,"elements":[5]}}}"#)
+            .unwrap()
+            .bits
+            .to_usizes(),
+        vec![0, 2]
+    );
+}
+
+#[cfg(feature = "use_serde")]
+#[test]
+fn test_ser_de_validate() {
+    // a zero element inside of the last block must not truncate the output
+    let bitvec = BitVec::from_slice(&[3, 130]);
+    let json = serde_json::to_string(&bitvec).unwrap();
+    assert_eq!(json, r#"{"storage":[8,0,4],"nbits":131}"#);
+    assert_eq!(serde_json::from_str::<BitVec>(&json).unwrap(), bitvec);
+
+    // missing trailing elements are zero
+    let bitvec = serde_json::from_str::<BitVec>(r#"{"storage":[8],"nbits":300}"#).unwrap();
+    assert_eq!(bitvec.len(), 300);
+    assert_eq!(bitvec.usizes().collect::<Vec<_>>(), vec![3]);
+    assert!(!bitvec.all());
+    let mut expected = BitVec::from_slice(&[3]);
+    expected.resize(300, false);
+    assert_eq!(bitvec, expected);
+
+    let de = |json: &str| {
+        serde_json::from_str::<BitVec>(json)
+            .map(|_| ())
+            .map_err(|e| std::string::ToString::to_string(&e))
+    };
+    assert!(de(r#"{"storage":[1,2],"nbits":64}"#)
+        .unwrap_err()
+        .contains("2 elements are too many for 64 bits"));
+    assert!(de(r#"{"storage":[1,0,0,0,1],"nbits":0}"#)
+        .unwrap_err()
+        .contains("5 elements are too many for 0 bits"));
+    assert!(de(r#"{"storage":[32],"nbits":5}"#)
+        .unwrap_err()
+        .contains("set bits beyond nbits 5"));
+    assert!(de(r#"{"storage":[0,1],"nbits":65}"#).is_ok());
+    assert!(de(r#"{"storage":[0,2],"nbits":65}"#)
+        .unwrap_err()
+        .contains("set bits beyond nbits 65"));
+
+    // nbits isn't trusted: no overflow, no allocation abort
+    assert!(de(r#"{"storage":[],"nbits":18446744073709551615}"#)
+        .unwrap_err()
+        .contains("too large to allocate"));
+    assert!(de(r#"{"storage":[],"nbits":1000000000000000}"#)
+        .unwrap_err()
+        .contains("too large to allocate"));
+    assert!(de(r#"{"storage":[1],"nbits":18446744073709551615}"#)
+        .unwrap_err()
+        .contains("too large to allocate"));
+}
+
+#[test]
+fn test_bitvec_binary() {
+    let bitvec = BitVec::from_slice(&[0, 9, 64, 66]);
+    let bytes = bitvec.to_bytes();
+    // the format is stable
+    assert_eq!(
+        bytes,
+        [
+            b"BVSD".as_slice(),
+            &[1, 8, 0, 0],
+            &67u64.to_le_bytes(),
+            &[1, 2, 0, 0, 0, 0, 0, 0],
+            &[5, 0, 0, 0, 0, 0, 0, 0],
+        ]
+        .concat()
+    );
+
+    fn check<B: BitBlock<L>, const L: usize>(bytes: &[u8], bitvec: &BitVec) {
+        let b = BitVecSimd::<B, L>::from_bytes(bytes).unwrap();
+        assert_eq!(b.len(), bitvec.len());
+        assert_eq!(
+            b.usizes().collect::<Vec<_>>(),
+            bitvec.usizes().collect::<Vec<_>>()
+        );
+        assert_eq!(BitVec::from_bytes(&b.to_bytes()).unwrap(), *bitvec);
+    }
+    for i in [0, 1, 7, 8, 9, 63, 64, 65, 127, 128, 255, 256, 257, 1000] {
+        let bitvec: BitVec = (0..i).map(|x| x % 3 == 0 || x == i - 1).collect();
+        let bytes = bitvec.to_bytes();
+        for_each_block!(check, &bytes, &bitvec);
+    }
+
+    // malformed input
+    let bytes = BitVecSimd::<u8x16, 16>::from_slice(&[0, 9]).to_bytes();
+    assert_eq!(bytes.len(), 18);
+    let with = |i: usize, x: u8| {
+        let mut b = bytes.clone();
+        b[i] = x;
+        b
+    };
+    assert!(BitVec::from_bytes(&bytes).is_ok());
+    assert_eq!(
+        BitVec::from_bytes(&with(0, b'X')),
+        Err(DecodeError::BadMagic)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(4, 2)),
+        Err(DecodeError::UnsupportedVersion(2))
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(5, 3)),
+        Err(DecodeError::UnsupportedElementWidth(3))
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(6, 1)),
+        Err(DecodeError::ReservedBytes)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(7, 0x80)),
+        Err(DecodeError::ReservedBytes)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(17, 4)),
+        Err(DecodeError::BitsBeyondLen)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(17, 2)).unwrap().to_usizes(),
+        vec![0, 9]
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(8, 17)),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(8, 8)),
+        Err(DecodeError::TrailingBytes)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(8, 16)).unwrap().to_usizes(),
+        vec![0, 9]
+    );
+    assert_eq!(
+        BitVec::from_bytes(&with(8, 9)),
+        Err(DecodeError::BitsBeyondLen)
+    );
+    assert_eq!(
+        BitVec::from_bytes(&bytes[..10]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_bitvec_read_write() {
+    fn check<B: BitBlock<L>, const L: usize>(bitvec: &BitVec) {
+        let b = BitVecSimd::<B, L>::from_bytes(&bitvec.to_bytes()).unwrap();
+        let mut file = Vec::new();
+        b.write_to(&mut file).unwrap();
+        assert_eq!(file, b.to_bytes());
+        assert_eq!(BitVecSimd::<B, L>::read_from(&file[..]).unwrap(), b);
+    }
+    for i in [0, 1, 7, 8, 9, 63, 64, 65, 127, 128, 255, 256, 257, 1000] {
+        let bitvec: BitVec = (0..i).map(|x| x % 3 == 0 || x == i - 1).collect();
+        for_each_block!(check, &bitvec);
+    }
+
+    // malformed input
+    let bytes = BitVecSimd::<u8x16, 16>::from_slice(&[0, 9]).to_bytes();
+    let err = BitVec::read_from(&bytes[..17]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+    let mut bytes = bytes;
+    bytes[17] = 4;
+    let err = BitVec::read_from(&bytes[..]).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_bitvec_roaring() {
+    // array containers, without runs
+    let bitvec = BitVec::from_slice(&[1, 2, 4, 65536 + 5]);
+    let bytes = bitvec.to_roaring();
+    assert_eq!(
+        bytes,
+        [
+            &[0x3a, 0x30, 0, 0, 2, 0, 0, 0][..],
+            &[0, 0, 2, 0, 1, 0, 0, 0],
+            &[24, 0, 0, 0, 30, 0, 0, 0],
+            &[1, 0, 2, 0, 4, 0],
+            &[5, 0],
+        ]
+        .concat()
+    );
+    assert_eq!(BitVec::from_roaring(&bytes).unwrap(), bitvec);
+
+    // a run container, without offsets
+    let bitvec = BitVec::ones(100);
+    let bytes = bitvec.to_roaring();
+    assert_eq!(bytes, [0x3b, 0x30, 0, 0, 1, 0, 0, 99, 0, 1, 0, 0, 0, 99, 0]);
+    assert_eq!(BitVec::from_roaring(&bytes).unwrap(), bitvec);
+
+    // empty
+    let bytes = BitVec::zeros(10).to_roaring();
+    assert_eq!(bytes, [0x3a, 0x30, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(BitVec::from_roaring(&bytes).unwrap(), BitVec::zeros(0));
+
+    // bitmap, array and run containers mixed
+    let mut bitvec: BitVec = (0..65536 * 5).map(|x| x % 3 == 0 && x < 65536).collect();
+    bitvec.set(65536 + 7, true);
+    bitvec.fill_range(65536 * 2..65536 * 3 + 100);
+    bitvec.set(65536 * 4 + 65535, true);
+    let bytes = bitvec.to_roaring();
+    assert_eq!(bytes[0..2], [0x3b, 0x30]);
+    assert_eq!(BitVec::from_roaring(&bytes).unwrap(), bitvec);
+    let bitvec2 = BitVecSimd::<u8x16, 16>::from_roaring(&bytes).unwrap();
+    assert_eq!(bitvec2.to_usizes(), bitvec.to_usizes());
+
+    // the reference files of the Roaring format specification, see testdata/README.md
+    let expected: BitVec = (0..100)
+        .map(|i| i * 1000)
+        .chain((100_000..200_000).map(|i| i * 3))
+        .chain(700_000..800_000)
+        .collect();
+    let without_runs = include_bytes!("../testdata/bitmapwithoutruns.bin");
+    let with_runs = include_bytes!("../testdata/bitmapwithruns.bin");
+    assert_eq!(BitVec::from_roaring(without_runs).unwrap(), expected);
+    assert_eq!(BitVec::from_roaring(with_runs).unwrap(), expected);
+    let bitvec = BitVecSimd::<u8x16, 16>::from_roaring(with_runs).unwrap();
+    assert!(bitvec.usizes().eq(expected.usizes()));
+    assert_eq!(expected.to_roaring(), with_runs);
+
+    // malformed input
+    let bytes = BitVec::from_slice(&[1, 2, 4]).to_roaring();
+    assert_eq!(
+        BitVec::from_roaring(&bytes[..3]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        BitVec::from_roaring(&bytes[..bytes.len() - 1]),
+        Err(DecodeError::UnexpectedEnd)
+    );
+    assert_eq!(
+        BitVec::from_roaring(&[&bytes[..], &[0]].concat()),
+        Err(DecodeError::TrailingBytes)
+    );
+    let mut bad = bytes.clone();
+    bad[0] = 0;
+    assert_eq!(BitVec::from_roaring(&bad), Err(DecodeError::BadMagic));
+    let mut bad = bytes.clone();
+    bad[bytes.len() - 2] = 1;
+    assert_eq!(
+        BitVec::from_roaring(&bad),
+        Err(DecodeError::InvalidContainer(0))
+    );
+    let mut bad = BitVec::ones(100).to_roaring();
+    bad[13] = 100;
+    assert_eq!(
+        BitVec::from_roaring(&bad),
+        Err(DecodeError::InvalidContainer(0))
+    );
+}
+
+#[test]
+fn test_bitvec_raw_view() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 7, 8, 9, 63, 64, 65, 127, 128, 129, 300] {
+            let bools: Vec<bool> = (0..nbits).map(|x| x % 5 == 1 || x == nbits - 1).collect();
+            let bitvec = BitVecSimd::<B, L>::from_bool_iterator(bools.iter().copied());
+            assert_eq!(bitvec.as_elements().len(), bitvec.storage_len() * L);
+            assert_eq!(
+                bitvec.as_bytes().len(),
+                bitvec.storage_len() * B::BIT_WIDTH / 8
+            );
+            for (i, b) in bools.iter().enumerate() {
+                let element = bitvec.as_elements()[i / B::ELEMENT_BIT_WIDTH];
+                let bit = element.wrapping_shr((i % B::ELEMENT_BIT_WIDTH) as u32) & B::ONE_ELEMENT;
+                assert_eq!(bit == B::ONE_ELEMENT, *b);
+            }
+
+            let mut bytes = vec![0u8; (nbits + 7) / 8];
+            for (i, b) in bools.iter().enumerate() {
+                bytes[i / 8] |= (*b as u8) << (i % 8);
+            }
+            assert_eq!(BitVecSimd::<B, L>::from_le_bytes(&bytes, nbits), bitvec);
+            if cfg!(target_endian = "little") {
+                assert_eq!(bitvec.as_bytes()[..bytes.len()], bytes[..]);
+            }
+            // stray bits in the input are ignored
+            bytes.push(0xff);
+            if nbits % 8 != 0 {
+                *bytes.iter_mut().rev().nth(1).unwrap() |= 0xff << (nbits % 8);
+            }
+            assert_eq!(BitVecSimd::<B, L>::from_le_bytes(&bytes, nbits), bitvec);
+
+            let mut bitvec2 = bitvec.clone();
+            for e in bitvec2.as_mut_elements().iter_mut() {
+                *e = !*e;
+            }
+            assert_eq!(bitvec2, bitvec.inverse());
+            assert_eq!(bitvec2.count_ones(), nbits - bitvec.count_ones());
+
+            // no set bits beyond nbits
+            let ones = BitVecSimd::<B, L>::ones(nbits);
+            assert_eq!(
+                ones.as_elements()
+                    .iter()
+                    .map(|x| x.count_ones() as usize)
+                    .sum::<usize>(),
+                nbits
+            );
+            let mut ones = BitVecSimd::<B, L>::zeros(nbits);
+            ones.set_all_true();
+            assert_eq!(
+                ones.as_elements()
+                    .iter()
+                    .map(|x| x.count_ones() as usize)
+                    .sum::<usize>(),
+                nbits
+            );
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_from_le_bytes_panic() {
+    BitVec::from_le_bytes(&[0xff], 9);
+}
+
+#[test]
+fn test_bitvec_storage() {
+    let storage = vec![u64x4::MAX; 3];
+    for nbits in [0, 1, 63, 64, 65, 255, 256, 257, 700, 768] {
+        let bitvec = BitVec::from_storage(storage.clone(), nbits);
+        assert_eq!(bitvec, BitVec::ones(nbits));
+        assert_eq!(bitvec.count_ones(), nbits);
+        assert!(bitvec.all());
+        assert_eq!(bitvec.inverse().count_ones(), 0);
+        let storage2 = bitvec.into_storage();
+        assert_eq!(storage2.len(), (nbits + 255) / 256);
+
+        let mut bitvec = BitVec::zeros(10);
+        let old = bitvec.replace_storage(storage.clone(), nbits);
+        assert_eq!(old.len(), 1);
+        assert_eq!(bitvec, BitVec::ones(nbits));
+
+        let mut bitvec = BitVec::zeros(1000);
+        unsafe { bitvec.set_raw_copy(storage.as_ptr() as *mut _, storage.len(), nbits) };
+        assert_eq!(bitvec, BitVec::ones(nbits));
+    }
+    let mut storage = storage;
+    let mut bitvec = BitVec::zeros(1000);
+    unsafe {
+        let ptr = storage.as_mut_ptr();
+        let (len, capacity) = (storage.len(), storage.capacity());
+        std::mem::forget(storage);
+        bitvec.set_raw(ptr, len, capacity, 300);
+    }
+    assert_eq!(bitvec, BitVec::ones(300));
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_from_storage_panic() {
+    BitVec::from_storage(vec![u64x4::MAX; 3], 769);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_dyn_bitvec() {
+    assert!(["avx2", "popcnt", "portable"].contains(&DynBitVec::backend()));
+    for (n1, n2) in [
+        (0, 0),
+        (1, 1),
+        (100, 100),
+        (1000, 1000),
+        (1000, 3000),
+        (3000, 257),
+    ] {
+        let b1: BitVec = (0..n1).map(|x| x % 3 == 0 || x % 7 == 1).collect();
+        let b2: BitVec = (0..n2).map(|x| x % 5 == 0 || x > 900).collect();
+        let (d1, d2) = (DynBitVec::new(b1.clone()), DynBitVec::from(b2.clone()));
+        assert_eq!(d1.count_ones(), b1.count_ones());
+        assert_eq!(d2.count_ones(), b2.count_ones());
+        assert_eq!(d1.and_count(&d2), b1.and_count(&b2));
+        assert_eq!(d1.or_count(&d2), b1.or_count(&b2));
+        assert_eq!(d1.xor_count(&d2), b1.xor_count(&b2));
+        assert_eq!(d1.andnot_count(&d2), b1.andnot_count(&b2));
+        assert_eq!(d2.andnot_count(&d1), b2.andnot_count(&b1));
+        assert_eq!(d1.hamming_distance(&d2), b1.hamming_distance(&b2));
+        assert_eq!(d1.jaccard(&d2), b1.jaccard(&b2));
+        assert_eq!(d1.tanimoto(&d2), b1.tanimoto(&b2));
+        assert_eq!(d1.dice(&d2), b1.dice(&b2));
+        assert_eq!(d1.cosine(&d2), b1.cosine(&b2));
+        if n1 == n2 {
+            assert_eq!(d1.and(&d2).into_inner(), b1.and_cloned(&b2));
+            assert_eq!(BitVec::from(d1.or(&d2)), b1.or_cloned(&b2));
+            let mut d3 = d1.clone();
+            d3.xor_inplace(&d2);
+            assert_eq!(*d3, b1.xor_cloned(&b2));
+            // methods of BitVec are available through Deref
+            d3.set(0, true);
+            assert_eq!(d3.get(0), Some(true));
+        }
+    }
+}
+
+#[test]
+#[cfg(feature = "std")]
+#[should_panic]
+fn test_dyn_bitvec_panic() {
+    let d1 = DynBitVec::new(BitVec::ones(10));
+    let d2 = DynBitVec::new(BitVec::ones(11));
+    d1.and(&d2);
+}
+
+#[test]
+fn test_block_layout() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        assert_eq!(B::LANES, L);
+        assert_eq!(B::ELEMENT_BIT_WIDTH * L, B::BIT_WIDTH);
+        assert_eq!(core::mem::size_of::<B>() * 8, B::BIT_WIDTH);
+        assert_eq!(
+            core::mem::size_of::<[B::Element; L]>(),
+            core::mem::size_of::<B>()
+        );
+        let mut arr = [B::ZERO_ELEMENT; L];
+        arr[L - 1] = B::ONE_ELEMENT;
+        assert!(B::from_array(arr).to_array() == arr);
+        assert_eq!(B::from_array([B::MAX_ELEMENT; L]), B::MAX);
+        assert_eq!(B::from_array([B::ZERO_ELEMENT; L]), B::ZERO);
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_block_count_ones() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut blocks = Vec::new();
+        for i in 0..200 {
+            let arr = [(); L].map(|_| {
+                let state = rng.next_u64();
+                let bytes = [state.to_le_bytes(), (!state).to_le_bytes()].concat();
+                match i {
+                    0 => B::ZERO_ELEMENT,
+                    1 => B::MAX_ELEMENT,
+                    2 => B::ONE_ELEMENT,
+                    _ => B::Element::from_le_slice(&bytes[..B::ELEMENT_BIT_WIDTH / 8]),
+                }
+            });
+            let expected: u32 = arr.iter().map(|x| x.count_ones()).sum();
+            assert_eq!(B::from_array(arr).count_ones(), expected as usize);
+            blocks.push(B::from_array(arr));
+        }
+        assert_eq!(B::ZERO.count_ones(), 0);
+        assert_eq!(B::MAX.count_ones(), B::BIT_WIDTH);
+
+        // groups of 16 go through the carry-save adders, the rest block by block
+        for n in (0..=40).chain([199, 200]) {
+            let expected: usize = blocks[..n].iter().map(|b| b.count_ones()).sum();
+            assert_eq!(count_ones_slice(&blocks[..n]), expected);
+            assert_eq!(count_ones_blocks(blocks[..n].iter().copied()), expected);
+        }
+        assert_eq!(count_ones_slice(&[B::MAX; 33]), 33 * B::BIT_WIDTH);
+        assert_eq!(count_ones_blocks([B::MAX; 33]), 33 * B::BIT_WIDTH);
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_bitvec_from_iter_extend() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let bools: Vec<bool> = (0..1000).map(|x| x % 3 == 0 || x % 7 == 1).collect();
+        for split in [
+            0, 1, 7, 8, 63, 64, 65, 128, 255, 256, 257, 511, 512, 999, 1000,
+        ] {
+            let expected = bools[..split]
+                .iter()
+                .enumerate()
+                .filter(|(_, b)| **b)
+                .map(|(i, _)| i)
+                .collect::<Vec<_>>();
+            let bitvec: BitVecSimd<B, L> = bools[..split].iter().copied().collect();
+            assert_eq!(bitvec.len(), split);
+            assert_eq!(bitvec.usizes().collect::<Vec<_>>(), expected);
+            assert_eq!(
+                bitvec.storage_len(),
+                (split + B::BIT_WIDTH - 1) / B::BIT_WIDTH
+            );
+
+            let mut bitvec2 = bitvec.clone();
+            bitvec2.extend(&bools[split..]);
+            assert_eq!(bitvec2.len(), bools.len());
+            assert_eq!(Vec::<bool>::from(bitvec2.clone()), bools);
+            // an iterator without a useful size hint, and extending by nothing
+            bitvec2.extend(core::iter::empty::<bool>());
+            let mut bitvec3 = bitvec;
+            bitvec3.extend(bools[split..].iter().copied().filter(|_| true));
+            assert_eq!(bitvec3, bitvec2);
+        }
+        let ones: BitVecSimd<B, L> = core::iter::repeat(true).take(300).collect();
+        assert_eq!(ones, BitVecSimd::ones(300));
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_bitvec_from_indices() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let sets: Vec<Vec<usize>> = vec![
+            vec![],
+            vec![0],
+            vec![1, 3, 5, 700],
+            (0..300).collect(),
+            (0..1000).rev().filter(|x| x % 7 == 3).collect(),
+            vec![129, 2, 64, 2, 0, 999, 63, 64],
+        ];
+        for set in sets {
+            let max = set.iter().max().map_or(0, |max| max + 1);
+            let mut expected = BitVecSimd::<B, L>::zeros(max);
+            for i in set.iter() {
+                expected.set(*i, true);
+            }
+
+            assert_eq!(BitVecSimd::<B, L>::from_slice(&set), expected);
+            let bitvec: BitVecSimd<B, L> = set.iter().copied().collect();
+            assert_eq!(bitvec, expected);
+
+            let bitvec = BitVecSimd::<B, L>::from_indices(set.iter().copied(), 1500);
+            expected.resize(1500, false);
+            assert_eq!(bitvec, expected);
+            assert_eq!(
+                bitvec.storage_len(),
+                (1500 + B::BIT_WIDTH - 1) / B::BIT_WIDTH
+            );
+            let mut sorted = set.clone();
+            sorted.sort_unstable();
+            sorted.dedup();
+            let bitvec = BitVecSimd::<B, L>::from_sorted_indices(sorted, 1500);
+            assert_eq!(bitvec, expected);
+
+            // extending never shrinks
+            let mut bitvec = BitVecSimd::<B, L>::zeros(1500);
+            bitvec.extend(set.iter().copied());
+            assert_eq!(bitvec, expected);
+            bitvec.extend(Some(2000));
+            assert_eq!(bitvec.len(), 2001);
+            assert_eq!(bitvec.count_ones(), expected.count_ones() + 1);
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_from_indices_panic() {
+    BitVec::from_indices([1, 10], 10);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_from_sorted_indices_panic() {
+    BitVec::from_sorted_indices([1, 10], 10);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "strictly increasing")]
+fn test_bitvec_from_sorted_indices_unsorted_panic() {
+    BitVec::from_sorted_indices([1, 5, 3], 10);
+}
+
+#[test]
+fn test_bitvec_push_pop_insert_remove() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+        let mut next = |n: usize| rng.below(n);
+        let mut bitvec = BitVecSimd::<B, L>::zeros(0);
+        let mut model: Vec<bool> = Vec::new();
+        for _ in 0..1000 {
+            let flag = next(3) == 0;
+            match next(10) {
+                0..=3 => {
+                    bitvec.push(flag);
+                    model.push(flag);
+                }
+                4 => assert_eq!(bitvec.pop(), model.pop()),
+                5 | 6 => {
+                    let index = next(model.len() + 1);
+                    bitvec.insert(index, flag);
+                    model.insert(index, flag);
+                }
+                7 if !model.is_empty() => {
+                    let index = next(model.len());
+                    assert_eq!(bitvec.remove(index), model.remove(index));
+                }
+                8 => {
+                    let n = next(300);
+                    let mut other: BitVecSimd<B, L> = (0..n).map(|x| x % 5 == 1).collect();
+                    bitvec.append(&mut other);
+                    assert!(other.is_empty());
+                    assert_eq!(other.storage_len(), 0);
+                    model.extend((0..n).map(|x| x % 5 == 1));
+                }
+                _ => {
+                    let other: BitVecSimd<B, L> = (0..300).map(|x| x % 3 == 0).collect();
+                    let (start, end) = (next(150), 150 + next(150));
+                    bitvec.extend_from_bitslice(&other.slice(start..end));
+                    model.extend((start..end).map(|x| x % 3 == 0));
+                }
+            }
+            assert_eq!(bitvec.len(), model.len());
+            assert_eq!(
+                bitvec.storage_len(),
+                (model.len() + B::BIT_WIDTH - 1) / B::BIT_WIDTH
+            );
+            assert_eq!(bitvec.count_ones(), model.iter().filter(|b| **b).count());
+        }
+        assert_eq!(Vec::<bool>::from(bitvec.clone()), model);
+        // the tail stays clear, so bits read back as zero after growing
+        let len = bitvec.len();
+        bitvec.resize(len + 500, false);
+        assert_eq!(bitvec.count_ones_before(len), bitvec.count_ones());
+        while bitvec.pop().is_some() {}
+        assert_eq!(bitvec, BitVecSimd::zeros(0));
+    }
+    for_each_block!(check);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_insert_panic() {
+    BitVec::zeros(10).insert(11, true);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_remove_panic() {
+    BitVec::zeros(10).remove(10);
+}
+
+#[test]
+fn test_bitvec_split_concat_chunks() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let bools: Vec<bool> = (0..1100).map(|x| x % 3 == 0 || x % 11 == 5).collect();
+        let bitvec: BitVecSimd<B, L> = bools.iter().copied().collect();
+        for at in [
+            0, 1, 63, 64, 65, 127, 128, 129, 256, 512, 1000, 1024, 1099, 1100,
+        ] {
+            let mut head = bitvec.clone();
+            let tail = head.split_off(at);
+            assert_eq!(Vec::<bool>::from(head.clone()), bools[..at]);
+            assert_eq!(Vec::<bool>::from(tail.clone()), bools[at..]);
+            assert_eq!(head.storage_len(), (at + B::BIT_WIDTH - 1) / B::BIT_WIDTH);
+            assert_eq!(
+                tail.storage_len(),
+                (bools.len() - at + B::BIT_WIDTH - 1) / B::BIT_WIDTH
+            );
+            assert_eq!(BitVecSimd::concat(&[head.clone(), tail.clone()]), bitvec);
+
+            let mut truncated = bitvec.clone();
+            truncated.truncate(at);
+            assert_eq!(truncated, head);
+            truncated.truncate(at + 1);
+            assert_eq!(truncated, head);
+
+            if at > 0 {
+                let chunks: Vec<_> = bitvec.chunks(at).collect();
+                assert_eq!(chunks.len(), (bools.len() + at - 1) / at);
+                assert_eq!(bitvec.chunks(at).len(), chunks.len());
+                for (c, b) in chunks.iter().zip(bools.chunks(at)) {
+                    assert_eq!(c.iter().collect::<Vec<_>>(), b);
+                }
+                let owned: Vec<_> = chunks.iter().map(|c| c.to_bitvec()).collect();
+                assert_eq!(BitVecSimd::concat(&owned), bitvec);
+
+                let exact = bitvec.chunks_exact(at);
+                let remainder = exact.remainder();
+                assert_eq!(exact.len(), bools.len() / at);
+                for (c, b) in exact.zip(bools.chunks_exact(at)) {
+                    assert_eq!(c.iter().collect::<Vec<_>>(), b);
+                }
+                let expected = bools.chunks_exact(at).remainder();
+                assert_eq!(remainder.iter().collect::<Vec<_>>(), expected);
+            }
+        }
+        let parts = [
+            BitVecSimd::<B, L>::ones(3),
+            BitVecSimd::zeros(0),
+            BitVecSimd::zeros(200),
+            BitVecSimd::ones(B::BIT_WIDTH),
+            BitVecSimd::ones(5),
+        ];
+        let bitvec = BitVecSimd::concat(&parts);
+        assert_eq!(bitvec.len(), 208 + B::BIT_WIDTH);
+        assert_eq!(bitvec.count_ones(), 8 + B::BIT_WIDTH);
+        assert_eq!(bitvec.count_ones_before(203), 3);
+        assert_eq!(BitVecSimd::<B, L>::concat(&[]), BitVecSimd::zeros(0));
+        assert_eq!(BitVecSimd::<B, L>::zeros(0).chunks(3).count(), 0);
+        assert_eq!(bitvec.chunks(usize::MAX).len(), 1);
+        assert_eq!(bitvec.chunks_exact(usize::MAX).len(), 0);
+    }
+    for_each_block!(check);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_split_off_panic() {
+    BitVec::zeros(10).split_off(11);
+}
+
+#[test]
+#[should_panic]
+fn test_bitvec_chunks_panic() {
+    BitVec::zeros(10).chunks(0);
+}
+
+#[test]
+fn test_bitvec_bit_ref_mut() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        for nbits in [0, 1, 7, 63, 64, 65, 300] {
+            let mut model: Vec<bool> = (0..nbits).map(|x| x % 3 == 0).collect();
+            let mut bitvec: BitVecSimd<B, L> = model.iter().copied().collect();
+
+            // every reference alive at once, dropped out of order
+            let mut refs: Vec<_> = bitvec.iter_mut().collect();
+            assert_eq!(refs.len(), nbits);
+            for (i, bit) in refs.iter_mut().enumerate() {
+                assert_eq!(**bit, model[i]);
+                if i % 2 == 0 {
+                    **bit = !**bit;
+                    model[i] = !model[i];
+                }
+            }
+            while !refs.is_empty() {
+                refs.swap_remove(refs.len() / 2);
+            }
+            drop(refs);
+            assert_eq!(Vec::<bool>::from(bitvec.clone()), model);
+
+            for (i, mut bit) in bitvec.iter_mut().rev().enumerate() {
+                *bit = i % 5 == 0;
+                model[nbits - 1 - i] = i % 5 == 0;
+            }
+            assert_eq!(Vec::<bool>::from(bitvec.clone()), model);
+
+            if nbits > 0 {
+                *bitvec.get_mut(nbits - 1).unwrap() = true;
+                model[nbits - 1] = true;
+            }
+            assert!(bitvec.get_mut(nbits).is_none());
+            assert_eq!(Vec::<bool>::from(bitvec.clone()), model);
+            // the tail beyond the length is untouched
+            bitvec.resize(nbits + 200, false);
+            assert_eq!(bitvec.count_ones(), model.iter().filter(|b| **b).count());
+        }
+    }
+    for_each_block!(check);
+}
+
+#[test]
+fn test_bitslice_usizes() {
+    fn check<B: BitBlock<L>, const L: usize>() {
+        let bitvec: BitVecSimd<B, L> = (0..700).map(|x| x % 3 == 0 || x % 64 == 63).collect();
+        for start in [0, 1, 63, 64, 65, 127, 128, 300, 511, 512, 699, 700] {
+            for end in [start, start + 1, start + 63, start + 64, start + 200, 700] {
+                if end > 700 {
+                    continue;
+                }
+                let slice = bitvec.slice(start..end);
+                let expected: Vec<usize> = (start..end)
+                    .filter(|x| x % 3 == 0 || x % 64 == 63)
+                    .map(|x| x - start)
+                    .collect();
+                assert_eq!(slice.usizes().collect::<Vec<_>>(), expected);
+                assert_eq!(slice.count_ones(), expected.len());
+                let bits: Vec<bool> = (start..end).map(|x| bitvec.get_unchecked(x)).collect();
+                assert_eq!(Vec::<bool>::from(slice.to_bitvec()), bits);
+            }
+        }
+    }
+    for_each_block!(check);
+}